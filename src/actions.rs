@@ -0,0 +1,366 @@
+use std::collections::BTreeMap;
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "bindings.ron";
+const SETTINGS_STORAGE_KEY: &str = "controls.ron";
+
+/// Turns keys, mouse and gamepad buttons into `ButtonInput<Action>`, through bindings kept between runs.
+///
+/// Systems read actions instead of raw buttons, so every control can be rebound.
+/// Any connected gamepad works, they can come and go while playing.
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Bindings::load())
+            .insert_resource(ControlSettings::load())
+            .init_resource::<ButtonInput<Action>>()
+            .init_resource::<VirtualActions>()
+            .add_systems(PreUpdate, update_actions.in_set(ActionsSystem).after(InputSystem))
+            .add_systems(Update, save_bindings.run_if(resource_changed::<Bindings>))
+            .add_systems(Update, save_settings.run_if(resource_changed::<ControlSettings>))
+            .add_systems(Update, log_gamepads)
+        ;
+    }
+}
+
+/// `ButtonInput<Action>` is up to date after this set.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ActionsSystem;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    /// Pauses the game, closes a menu.
+    Pause,
+    /// Cheat, kills every alien of the wave.
+    SkipWave,
+    DebugInfo,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    /// Focuses the next menu item in reading order.
+    MenuNext,
+    /// Presses the focused menu item.
+    Confirm,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Pause,
+        Action::SkipWave,
+        Action::DebugInfo,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuLeft,
+        Action::MenuRight,
+        Action::MenuNext,
+        Action::Confirm,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Fire => "Fire",
+            Action::Pause => "Pause",
+            Action::SkipWave => "Skip wave",
+            Action::DebugInfo => "Debug info",
+            Action::MenuUp => "Menu up",
+            Action::MenuDown => "Menu down",
+            Action::MenuLeft => "Menu left",
+            Action::MenuRight => "Menu right",
+            Action::MenuNext => "Menu next",
+            Action::Confirm => "Confirm",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    /// A key pressed while holding Alt.
+    AltKey(KeyCode),
+    Mouse(MouseButton),
+    Pad(GamepadButtonType),
+    /// A gamepad axis pushed more than half way, towards its positive end or not.
+    PadAxis(GamepadAxisType, bool),
+}
+
+impl Binding {
+    pub fn is_pad(self) -> bool {
+        matches!(self, Binding::Pad(_) | Binding::PadAxis(..))
+    }
+
+    pub fn name(self) -> String {
+        fn key_name(key: KeyCode) -> String {
+            let name = format!("{:?}", key);
+            name.strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .or_else(|| name.strip_prefix("Arrow"))
+                .unwrap_or(&name)
+                .to_owned()
+        }
+        match self {
+            Binding::Key(key) => key_name(key),
+            Binding::AltKey(key) => format!("Alt+{}", key_name(key)),
+            Binding::Mouse(MouseButton::Left) => "LMB".into(),
+            Binding::Mouse(MouseButton::Right) => "RMB".into(),
+            Binding::Mouse(MouseButton::Middle) => "MMB".into(),
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+            Binding::Pad(button) => match button {
+                GamepadButtonType::South => "Pad A".into(),
+                GamepadButtonType::East => "Pad B".into(),
+                GamepadButtonType::West => "Pad X".into(),
+                GamepadButtonType::North => "Pad Y".into(),
+                GamepadButtonType::LeftTrigger => "LB".into(),
+                GamepadButtonType::RightTrigger => "RB".into(),
+                GamepadButtonType::LeftTrigger2 => "LT".into(),
+                GamepadButtonType::RightTrigger2 => "RT".into(),
+                GamepadButtonType::DPadUp => "D-pad up".into(),
+                GamepadButtonType::DPadDown => "D-pad down".into(),
+                GamepadButtonType::DPadLeft => "D-pad left".into(),
+                GamepadButtonType::DPadRight => "D-pad right".into(),
+                button => format!("{:?}", button),
+            },
+            Binding::PadAxis(axis, positive) => {
+                let (stick, horizontal) = match axis {
+                    GamepadAxisType::LeftStickX => ("Left stick", true),
+                    GamepadAxisType::LeftStickY => ("Left stick", false),
+                    GamepadAxisType::RightStickX => ("Right stick", true),
+                    GamepadAxisType::RightStickY => ("Right stick", false),
+                    axis => return format!("{:?} {}", axis, if positive { "+" } else { "-" }),
+                };
+                let direction = match (horizontal, positive) {
+                    (true, true) => "right",
+                    (true, false) => "left",
+                    (false, true) => "up",
+                    (false, false) => "down",
+                };
+                format!("{} {}", stick, direction)
+            }
+        }
+    }
+}
+
+/// How the bat is steered.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum BatControl {
+    /// Move actions and the gamepad stick.
+    #[default]
+    Buttons,
+    /// The bat chases the mouse cursor, move actions still work while it is outside the window.
+    Mouse,
+}
+
+impl BatControl {
+    pub fn label(self) -> &'static str {
+        match self {
+            BatControl::Buttons => "keys",
+            BatControl::Mouse => "mouse",
+        }
+    }
+}
+
+/// Control options besides the bindings, kept between runs.
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub bat: BatControl,
+}
+
+impl ControlSettings {
+    fn load() -> Self {
+        let Some(text) = storage::read(SETTINGS_STORAGE_KEY) else {
+            return default();
+        };
+        ron::from_str(&text).unwrap_or_else(|e| {
+            error!("unable to read control settings: {}", e);
+            default()
+        })
+    }
+}
+
+/// Actions held by on-screen controls this frame, on top of the bound inputs.
+///
+/// Filled before `ActionsSystem` by whoever draws the controls.
+#[derive(Resource, Default)]
+pub struct VirtualActions(pub HashSet<Action>);
+
+/// The raw inputs bindings refer to.
+#[derive(SystemParam)]
+pub struct RawInputs<'w> {
+    pub keys: Res<'w, ButtonInput<KeyCode>>,
+    pub mouse: Res<'w, ButtonInput<MouseButton>>,
+    pub gamepads: Res<'w, Gamepads>,
+    pub pad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    pub pad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl RawInputs<'_> {
+    fn pressed(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys.pressed(key),
+            Binding::AltKey(key) => self.keys.pressed(key) && self.keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+            Binding::Mouse(button) => self.mouse.pressed(button),
+            Binding::Pad(button) => self.gamepads.iter().any(|pad| self.pad_buttons.pressed(GamepadButton::new(pad, button))),
+            Binding::PadAxis(axis, positive) => self.gamepads.iter().any(|pad| {
+                let value = self.pad_axes.get(GamepadAxis::new(pad, axis)).unwrap_or(0.0);
+                if positive { value > 0.5 } else { value < -0.5 }
+            }),
+        }
+    }
+
+    /// First gamepad button pressed this frame.
+    pub fn pad_just_pressed(&self) -> Option<GamepadButtonType> {
+        self.pad_buttons.get_just_pressed().next().map(|b| b.button_type)
+    }
+
+    /// Position of the first gamepad stick pushed on `axis`, from -1 to 1.
+    pub fn pad_axis(&self, axis: GamepadAxisType) -> f32 {
+        self.gamepads.iter()
+            .filter_map(|pad| self.pad_axes.get(GamepadAxis::new(pad, axis)))
+            .find(|value| *value != 0.0)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Inputs of every action, any of them triggers it.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct Bindings(BTreeMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (Action::MoveLeft, vec![Binding::Key(KeyCode::KeyA), Binding::Key(KeyCode::ArrowLeft), Binding::Pad(GamepadButtonType::DPadLeft)]),
+            (Action::MoveRight, vec![Binding::Key(KeyCode::KeyD), Binding::Key(KeyCode::ArrowRight), Binding::Pad(GamepadButtonType::DPadRight)]),
+            (Action::Fire, vec![Binding::Mouse(MouseButton::Left), Binding::Key(KeyCode::Space), Binding::Pad(GamepadButtonType::South)]),
+            (Action::Pause, vec![Binding::Key(KeyCode::Escape), Binding::Pad(GamepadButtonType::Start)]),
+            (Action::SkipWave, vec![Binding::AltKey(KeyCode::KeyZ)]),
+            (Action::DebugInfo, vec![Binding::AltKey(KeyCode::KeyF)]),
+            (Action::MenuUp, vec![Binding::Key(KeyCode::ArrowUp), Binding::Pad(GamepadButtonType::DPadUp), Binding::PadAxis(GamepadAxisType::LeftStickY, true)]),
+            (Action::MenuDown, vec![Binding::Key(KeyCode::ArrowDown), Binding::Pad(GamepadButtonType::DPadDown), Binding::PadAxis(GamepadAxisType::LeftStickY, false)]),
+            (Action::MenuLeft, vec![Binding::Key(KeyCode::ArrowLeft), Binding::Pad(GamepadButtonType::DPadLeft), Binding::PadAxis(GamepadAxisType::LeftStickX, false)]),
+            (Action::MenuRight, vec![Binding::Key(KeyCode::ArrowRight), Binding::Pad(GamepadButtonType::DPadRight), Binding::PadAxis(GamepadAxisType::LeftStickX, true)]),
+            (Action::MenuNext, vec![Binding::Key(KeyCode::Tab)]),
+            (Action::Confirm, vec![Binding::Key(KeyCode::Enter), Binding::Key(KeyCode::NumpadEnter), Binding::Pad(GamepadButtonType::South)]),
+        ]))
+    }
+}
+
+impl Bindings {
+    /// Most bindings kept per action, adding another drops the oldest.
+    pub const MAX: usize = 4;
+
+    fn load() -> Self {
+        let Some(text) = storage::read(STORAGE_KEY) else {
+            return default();
+        };
+        let mut bindings = ron::from_str::<Self>(&text).unwrap_or_else(|e| {
+            error!("unable to read key bindings: {}", e);
+            default()
+        });
+        // actions added since the file was written
+        for (action, defaults) in Self::default().0 {
+            bindings.0.entry(action).or_insert(defaults);
+        }
+        bindings
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Names of the bindings of `action`, like "A or Left".
+    pub fn names(&self, action: Action) -> String {
+        self.names_where(action, |_| true)
+    }
+
+    /// Names of the bindings of `action` on the gamepad, or off it.
+    pub fn pad_names(&self, action: Action, pad: bool) -> String {
+        self.names_where(action, |b| b.is_pad() == pad)
+    }
+
+    fn names_where(&self, action: Action, filter: impl Fn(Binding) -> bool) -> String {
+        let names = self.get(action).iter().copied().filter(|b| filter(*b)).map(Binding::name).collect::<Vec<_>>();
+        if names.is_empty() {
+            "unbound".into()
+        } else {
+            names.join(" or ")
+        }
+    }
+
+    /// Binds `binding` to `action` as well. Other actions keep it, menus and gameplay share buttons.
+    pub fn add(&mut self, action: Action, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|b| *b != binding);
+        bindings.push(binding);
+        if bindings.len() > Self::MAX {
+            bindings.remove(0);
+        }
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.0.entry(action).or_default().clear();
+    }
+}
+
+fn update_actions(
+    inputs: RawInputs,
+    bindings: Res<Bindings>,
+    virtual_actions: Res<VirtualActions>,
+    mut actions: ResMut<ButtonInput<Action>>,
+) {
+    actions.clear();
+    for action in Action::ALL {
+        if bindings.get(action).iter().any(|b| inputs.pressed(*b)) || virtual_actions.0.contains(&action) {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+fn log_gamepads(mut events: EventReader<GamepadConnectionEvent>) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => info!("gamepad {} connected: {}", event.gamepad.id, info.name),
+            GamepadConnection::Disconnected => info!("gamepad {} disconnected", event.gamepad.id),
+        }
+    }
+}
+
+fn save_bindings(bindings: Res<Bindings>) {
+    if bindings.is_added() {
+        return;
+    }
+    match ron::ser::to_string_pretty(&*bindings, default()) {
+        Ok(text) => storage::write(STORAGE_KEY, &text),
+        Err(e) => error!("unable to save key bindings: {}", e),
+    }
+}
+
+fn save_settings(settings: Res<ControlSettings>) {
+    if settings.is_added() {
+        return;
+    }
+    match ron::ser::to_string_pretty(&*settings, default()) {
+        Ok(text) => storage::write(SETTINGS_STORAGE_KEY, &text),
+        Err(e) => error!("unable to save control settings: {}", e),
+    }
+}
@@ -0,0 +1,240 @@
+use bevy::core::FrameCount;
+use bevy::prelude::*;
+
+use crate::actions::{Action, BatControl, Binding, Bindings, ControlSettings, RawInputs};
+use crate::focus::FocusDefault;
+use crate::main_menu::MMenuStyles;
+use crate::MainState;
+
+/// Lists the bindings of every action and lets the player add or clear them.
+pub struct ControlsMenuPlugin<T: States> {
+    mystate: T,
+}
+
+impl<T: States+Copy> ControlsMenuPlugin<T> {
+    pub fn for_state(state: T) -> Self {
+        Self {
+            mystate: state
+        }
+    }
+}
+
+impl<T: States+Copy> Plugin for ControlsMenuPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(self.mystate), controls_setup)
+            .add_systems(Update, (controls_click, controls_keys, controls_text).chain().run_if(in_state(self.mystate)))
+            .add_systems(Update, controls_highlight.run_if(in_state(self.mystate)))
+            .add_systems(Update, controls_bat_mode.run_if(in_state(self.mystate)))
+            .add_systems(OnExit(self.mystate), controls_onexit)
+        ;
+    }
+}
+
+#[derive(Component)]
+struct MControls;
+
+#[derive(Component)]
+struct MControlsRow(Action);
+
+/// The row waits for the input to bind, since frame `started`.
+#[derive(Component)]
+struct MControlsCapture {
+    started: u32,
+}
+
+#[derive(Component)]
+struct MControlsDefaults;
+
+#[derive(Component)]
+struct MControlsBack;
+
+/// Switches between the bat control modes.
+#[derive(Component)]
+struct MControlsBatMode;
+
+fn controls_setup(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    settings: Res<ControlSettings>,
+) {
+    let style = TextStyle {
+        font: assets.load("eight-bit-dragon.otf"),
+        font_size: 25.0,
+        color: Color::rgb_u8(81, 130, 155),
+    };
+    let item = |text: &str, left: f32, top: f32, style: &TextStyle| TextBundle {
+        text: Text::from_section(text, style.clone()),
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(left),
+            top: Val::Percent(top),
+            ..default()
+        },
+        ..default()
+    };
+    for (i, action) in Action::ALL.into_iter().enumerate() {
+        let mut row = commands.spawn((
+            item("", 10.0, 5.0 + 5.5 * i as f32, &style),
+            Interaction::default(),
+            MControls,
+            MControlsRow(action),
+        ));
+        if i == 0 {
+            row.insert(FocusDefault);
+        }
+    }
+    commands.spawn((
+        item("Defaults", 10.0, 85.0, &style),
+        Interaction::default(),
+        MControls,
+        MControlsDefaults,
+    ));
+    commands.spawn((
+        item("Back", 40.0, 85.0, &style),
+        Interaction::default(),
+        MControls,
+        MControlsBack,
+    ));
+    commands.spawn((
+        item(&bat_mode_text(settings.bat), 60.0, 85.0, &style),
+        Interaction::default(),
+        MControls,
+        MControlsBatMode,
+    ));
+    let hint_style = TextStyle { font_size: 20.0, ..style.clone() };
+    commands.spawn((
+        item("Pick an action, then press a key, mouse or gamepad button to add it.\n\
+              Backspace clears its bindings, Esc cancels.", 10.0, 70.0, &hint_style),
+        MControls,
+    ));
+}
+
+/// Controls screen items of type `T` whose interaction changed this frame.
+type Interacted<T> = (With<T>, Changed<Interaction>);
+
+#[allow(clippy::too_many_arguments)]
+fn controls_click(
+    mut commands: Commands,
+    qrows: Query<(Entity, &Interaction), Interacted<MControlsRow>>,
+    qdefaults: Query<&Interaction, Interacted<MControlsDefaults>>,
+    qback: Query<&Interaction, Interacted<MControlsBack>>,
+    qcapture: Query<(), With<MControlsCapture>>,
+    mut bindings: ResMut<Bindings>,
+    mut state: ResMut<NextState<MainState>>,
+    frame: Res<FrameCount>,
+) {
+    // the next click is what gets bound
+    if !qcapture.is_empty() {
+        return;
+    }
+    for (e, int) in qrows.iter() {
+        if *int == Interaction::Pressed {
+            commands.entity(e).insert(MControlsCapture { started: frame.0 });
+        }
+    }
+    if let Ok(Interaction::Pressed) = qdefaults.get_single() {
+        *bindings = default();
+    }
+    if let Ok(Interaction::Pressed) = qback.get_single() {
+        state.set(MainState::MainMenu);
+    }
+}
+
+/// Binds the first input pressed while a row waits for one, else goes back on `Action::Pause`.
+fn controls_keys(
+    mut commands: Commands,
+    qcapture: Query<(Entity, &MControlsRow, &MControlsCapture)>,
+    inputs: RawInputs,
+    actions: Res<ButtonInput<Action>>,
+    mut bindings: ResMut<Bindings>,
+    mut state: ResMut<NextState<MainState>>,
+    frame: Res<FrameCount>,
+) {
+    let Ok((e, MControlsRow(action), capture)) = qcapture.get_single() else {
+        if actions.just_pressed(Action::Pause) {
+            state.set(MainState::MainMenu);
+        }
+        return;
+    };
+    // the click that started it
+    if capture.started == frame.0 {
+        return;
+    }
+    let alt = inputs.keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let key = inputs.keys.get_just_pressed().find(|k| !matches!(k, KeyCode::AltLeft | KeyCode::AltRight));
+    let binding = match key {
+        Some(KeyCode::Escape) => None,
+        Some(KeyCode::Backspace) => {
+            bindings.clear(*action);
+            None
+        }
+        Some(&key) => Some(if alt { Binding::AltKey(key) } else { Binding::Key(key) }),
+        None => match (inputs.mouse.get_just_pressed().next(), inputs.pad_just_pressed()) {
+            (Some(&button), _) => Some(Binding::Mouse(button)),
+            (None, Some(button)) => Some(Binding::Pad(button)),
+            (None, None) => return,
+        },
+    };
+    if let Some(binding) = binding {
+        bindings.add(*action, binding);
+    }
+    commands.entity(e).remove::<MControlsCapture>();
+}
+
+fn controls_text(
+    mut qrows: Query<(&MControlsRow, Has<MControlsCapture>, &mut Text)>,
+    bindings: Res<Bindings>,
+) {
+    for (MControlsRow(action), capturing, mut text) in qrows.iter_mut() {
+        let value = if capturing {
+            format!("{}: press a key...", action.label())
+        } else {
+            format!("{}: {}", action.label(), bindings.names(*action))
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn bat_mode_text(mode: BatControl) -> String {
+    format!("Bat: {}", mode.label())
+}
+
+fn controls_bat_mode(
+    mut q: Query<(&Interaction, &mut Text), Interacted<MControlsBatMode>>,
+    qcapture: Query<(), With<MControlsCapture>>,
+    mut settings: ResMut<ControlSettings>,
+) {
+    let Ok((Interaction::Pressed, mut text)) = q.get_single_mut() else {
+        return;
+    };
+    if !qcapture.is_empty() {
+        return;
+    }
+    settings.bat = match settings.bat {
+        BatControl::Buttons => BatControl::Mouse,
+        BatControl::Mouse => BatControl::Buttons,
+    };
+    text.sections[0].value = bat_mode_text(settings.bat);
+}
+
+fn controls_highlight(
+    mut q: Query<(&Interaction, &mut BackgroundColor), Interacted<MControls>>,
+    styles: Res<MMenuStyles>,
+) {
+    for (int, mut color) in q.iter_mut() {
+        match int {
+            Interaction::Hovered => { *color = BackgroundColor(styles.hover_color); }
+            Interaction::Pressed => {}
+            Interaction::None => { *color = BackgroundColor(Color::NONE); }
+        }
+    }
+}
+
+fn controls_onexit(mut commands: Commands, q: Query<Entity, With<MControls>>) {
+    for entity in q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+
+use crate::actions::{Action, ActionsSystem};
+use crate::main_menu::MMenuStyles;
+
+/// Menu navigation without a mouse: the menu actions move a focus between the UI nodes
+/// with an `Interaction`, `Action::Confirm` presses the focused one.
+///
+/// Menus keep reacting to `Interaction` only, a confirmed item looks clicked for one frame.
+/// A menu marks the item focused when it opens with `FocusDefault`, hovering an item focuses it too.
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Focus>()
+            .add_systems(PreUpdate, navigate.after(UiSystem::Focus).after(ActionsSystem))
+            .add_systems(PostUpdate, highlight)
+        ;
+    }
+}
+
+/// The focused menu item, if any.
+#[derive(Resource, Default)]
+pub struct Focus {
+    entity: Option<Entity>,
+    /// Item pressed by the last `Action::Confirm`, released the frame after.
+    pressed: Option<Entity>,
+    /// Item that shows the highlight.
+    highlighted: Option<Entity>,
+}
+
+impl Focus {
+    pub fn get(&self) -> Option<Entity> {
+        self.entity
+    }
+}
+
+/// Item focused while nothing else is, one per menu.
+#[derive(Component)]
+pub struct FocusDefault;
+
+/// A node with an `Interaction` that is not a menu item, the focus skips it.
+#[derive(Component)]
+pub struct NoFocus;
+
+/// Nodes the focus can move to.
+type Focusable = (With<Node>, Without<NoFocus>);
+
+fn navigate(
+    mut focus_res: ResMut<Focus>,
+    mut q: Query<(Entity, &mut Interaction, &GlobalTransform, &InheritedVisibility), Focusable>,
+    qdefault: Query<(), With<FocusDefault>>,
+    actions: Res<ButtonInput<Action>>,
+) {
+    // only a new focused item counts as a change
+    let focus = focus_res.bypass_change_detection();
+    let before = focus.entity;
+    if let Some((_, mut int, _, _)) = focus.pressed.take().and_then(|e| q.get_mut(e).ok()) {
+        if *int == Interaction::Pressed {
+            *int = Interaction::None;
+        }
+    }
+    if focus.entity.is_some_and(|e| !q.get(e).is_ok_and(|(_, _, _, v)| v.get())) {
+        focus.entity = None;
+    }
+    if let Some((e, _, _, _)) = q.iter_mut().find(|(_, int, _, _)| int.is_changed() && **int == Interaction::Hovered) {
+        focus.entity = Some(e);
+    }
+    if focus.entity.is_none() {
+        focus.entity = q.iter().find(|(e, _, _, v)| v.get() && qdefault.contains(*e)).map(|(e, _, _, _)| e);
+    }
+
+    let direction = [
+        (Action::MenuUp, Vec2::NEG_Y),
+        (Action::MenuDown, Vec2::Y),
+        (Action::MenuLeft, Vec2::NEG_X),
+        (Action::MenuRight, Vec2::X),
+    ].into_iter().find(|(action, _)| actions.just_pressed(*action)).map(|(_, dir)| dir);
+    if let Some(dir) = direction {
+        let items = q.iter().filter(|(_, _, _, v)| v.get()).map(|(e, _, t, _)| (e, t.translation().xy()));
+        focus.entity = match focus.entity.and_then(|e| q.get(e).ok()) {
+            // nearest item that way, straying off the line costs extra
+            Some((_, _, from, _)) => {
+                let from = from.translation().xy();
+                items
+                    .map(|(e, pos)| (e, pos - from))
+                    .filter(|(_, d)| d.dot(dir) > 0.0)
+                    .min_by(|(_, a), (_, b)| cost(*a, dir).total_cmp(&cost(*b, dir)))
+                    .map(|(e, _)| e)
+                    .or(focus.entity)
+            }
+            // first the top left one
+            None => items.min_by(|(_, a), (_, b)| reading_order(*a, *b)).map(|(e, _)| e),
+        };
+    }
+    if actions.just_pressed(Action::MenuNext) {
+        // reading order, wrapping around after the last item
+        let mut items = q.iter().filter(|(_, _, _, v)| v.get()).map(|(e, _, t, _)| (e, t.translation().xy())).collect::<Vec<_>>();
+        items.sort_by(|(_, a), (_, b)| reading_order(*a, *b));
+        let next = focus.entity.and_then(|f| items.iter().position(|(e, _)| *e == f)).map_or(0, |i| i + 1);
+        focus.entity = items.get(next).or(items.first()).map(|(e, _)| *e);
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        if let Some((e, mut int, _, _)) = focus.entity.and_then(|e| q.get_mut(e).ok()) {
+            *int = Interaction::Pressed;
+            focus.pressed = Some(e);
+        }
+    }
+    if focus.entity != before {
+        focus_res.set_changed();
+    }
+}
+
+fn cost(d: Vec2, dir: Vec2) -> f32 {
+    d.dot(dir) + 2.0 * d.perp_dot(dir).abs()
+}
+
+/// Top to bottom, then left to right. Total, so a NaN position from a zero sized layout can't panic.
+fn reading_order(a: Vec2, b: Vec2) -> std::cmp::Ordering {
+    a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+}
+
+fn highlight(
+    mut focus: ResMut<Focus>,
+    mut q: Query<&mut BackgroundColor>,
+    styles: Res<MMenuStyles>,
+) {
+    let focus = focus.bypass_change_detection();
+    if focus.highlighted != focus.entity {
+        if let Some(mut color) = focus.highlighted.and_then(|e| q.get_mut(e).ok()) {
+            *color = BackgroundColor(Color::NONE);
+        }
+        focus.highlighted = focus.entity;
+    }
+    if let Some(mut color) = focus.entity.and_then(|e| q.get_mut(e).ok()) {
+        // mouse hover systems clear it when the pointer leaves
+        if color.0 != styles.hover_color {
+            *color = BackgroundColor(styles.hover_color);
+        }
+    }
+}
@@ -2,6 +2,9 @@ use bevy::prelude::*;
 use bevy::app::{App, Update};
 use bevy::prelude::PositionType::Absolute;
 
+use crate::actions::Action;
+use crate::focus::NoFocus;
+
 #[derive(Component)]
 struct MFpsCounter;
 
@@ -11,6 +14,17 @@ struct MCursorPos;
 #[derive(Component)]
 struct MDebugText;
 
+#[derive(Component)]
+struct MDebugInfo;
+
+/// World position of the mouse cursor, `None` while it is outside the window.
+#[derive(Resource, Default, PartialEq)]
+pub struct CursorWorldPos(pub Option<Vec2>);
+
+/// Extra text other plugins want to show in the debug overlay.
+#[derive(Resource, Default)]
+pub struct DebugInfo(pub String);
+
 #[derive(Resource, Default)]
 struct FpsCounter {
     last_update: u128,
@@ -38,11 +52,14 @@ impl Plugin for FpsCounterPlugin {
                 fps_calc(update_delta, time, counter, q)
             })
             // .insert_resource(FpsCounter { update_delta: self.update_delta, ..default() })
+            .init_resource::<DebugInfo>()
+            .init_resource::<CursorWorldPos>()
             .add_systems(Startup, fpscounter_setup)
 
             .add_systems(Update, key_input)
             .add_systems(Update, fps_interact)
             .add_systems(Update, cursor_pos)
+            .add_systems(Update, debug_info.run_if(resource_changed::<DebugInfo>))
         ;
     }
 }
@@ -56,6 +73,7 @@ fn fpscounter_setup(mut commands: Commands) {
                 ..default()
             },
             Interaction::default(),
+            NoFocus,
             MFpsCounter,
             MDebugText
         )).insert(Visibility::Hidden);
@@ -69,6 +87,25 @@ fn fpscounter_setup(mut commands: Commands) {
             MCursorPos,
             MDebugText
         )).insert(Visibility::Hidden);
+    commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", TextStyle{color:Color::RED,font_size:15.,..default()}),
+                style: Style { position_type: Absolute, right: Val::Percent(0.0), bottom: Val::Percent(0.0),..default()},
+                ..default()
+            },
+            MDebugInfo,
+            MDebugText
+        )).insert(Visibility::Hidden);
+}
+
+fn debug_info(
+    info: Res<DebugInfo>,
+    mut q: Query<&mut Text, With<MDebugInfo>>
+) {
+    if let Some(mut txt) = q.iter_mut().next() {
+        txt.sections[0].value = info.0.clone();
+    }
 }
 
 fn fps_calc(
@@ -111,10 +148,12 @@ fn fps_interact(
 fn cursor_pos(
     window: Query<&Window>,
     camera: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
-    mut q: Query<&mut Text, With<MCursorPos>>
+    mut q: Query<&mut Text, With<MCursorPos>>,
+    mut world_pos: ResMut<CursorWorldPos>,
 ) {
     let (camera, cam_transform, projection) = camera.single();
     let Some(cursor_pos) = window.single().cursor_position() else {
+        world_pos.set_if_neq(CursorWorldPos(None));
         return;
     };
 
@@ -122,6 +161,7 @@ fn cursor_pos(
         error!("unable to transform cursor pos to world pos");
         return;
     };
+    world_pos.set_if_neq(CursorWorldPos(Some(pos)));
     // println!("mouse {:?} {:?} {:?}", window.single().cursor_position(), pos, projection);
     let Some(mut txt) = q.iter_mut().next() else {
         return;
@@ -134,11 +174,10 @@ fn cursor_pos(
 }
 
 fn key_input(
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<Action>>,
     mut qvisibility: Query<&mut Visibility, With<MDebugText>>
 ) {
-    if input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
-        && input.just_pressed(KeyCode::KeyF) {
+    if actions.just_pressed(Action::DebugInfo) {
         for mut v in qvisibility.iter_mut() {
             *v = match *v {
                 Visibility::Hidden => Visibility::Visible,
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "highscores.ron";
+
+/// Loads the high score table at startup, it is saved whenever it changes.
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load());
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScore {
+    pub name: String,
+    pub score: i32,
+    pub time: f32,
+    pub wave: u32,
+    pub date: String,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+    /// Name used for the previous entry, offered again for the next one.
+    pub name: String,
+    /// Entry added by the last game.
+    #[serde(skip)]
+    pub last: Option<usize>,
+}
+
+impl HighScores {
+    pub const SIZE: usize = 10;
+    pub const NAME_LEN: usize = 12;
+
+    /// Adds an entry if it makes it into the table and returns its position.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let pos = self.entries.iter().position(|e| e.score < entry.score).unwrap_or(self.entries.len());
+        self.last = None;
+        if pos >= Self::SIZE {
+            return None;
+        }
+        self.entries.insert(pos, entry);
+        self.entries.truncate(Self::SIZE);
+        self.last = Some(pos);
+        self.last
+    }
+
+    pub fn load() -> Self {
+        let Some(text) = storage::read(STORAGE_KEY) else {
+            return default();
+        };
+        ron::from_str(&text).unwrap_or_else(|e| {
+            error!("unable to read high scores: {}", e);
+            default()
+        })
+    }
+
+    pub fn save(&self) {
+        match ron::ser::to_string_pretty(self, default()) {
+            Ok(text) => storage::write(STORAGE_KEY, &text),
+            Err(e) => error!("unable to save high scores: {}", e),
+        }
+    }
+}
+
+/// Today as `YYYY-MM-DD` (UTC).
+pub fn today() -> String {
+    let days = storage::unix_time() / 86400;
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
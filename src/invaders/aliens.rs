@@ -1,118 +1,231 @@
 use bevy::math::Rect;
+use bevy::utils::HashMap;
 use bevy::prelude::*;
-use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::ecs::system::EntityCommands;
+use bevy::sprite::Mesh2dHandle;
 use rand::{Rng};
-use crate::invaders::{InvadersGame, InvaderState, MInvaders};
+use crate::invaders::config::{AlienShape, Arena, InvadersConfig};
+use crate::invaders::{GameClock, InvadersGame, InvadersRng, InvadersSet, InvaderState, MInvaders};
+use crate::invaders::interpolation::Interpolated;
+use crate::invaders::save::{self, ReflectSave, SaveRestore};
+use crate::invaders::sprite::SpriteSheet;
+use crate::invaders::pixel_art;
 use crate::invaders::bat::MBat;
-use crate::invaders::bullet::{MAlienBullet, MBullet, ShootEvent};
+use crate::invaders::bullet::ShootEvent;
+use crate::invaders::collision::{Collision, Hitbox, Layer};
+use crate::invaders::events::{AlienKilled, FormationStepped, WaveCleared};
+use crate::invaders::input::PlayerInput;
 use crate::MainState;
 
 
 pub struct AliensPlugin<T: States+Copy> {
     mystate: T,
+    presentation: bool,
 }
 
 impl<T:States+Copy> AliensPlugin<T> {
     pub fn for_state(state: T) -> Self {
         Self {
             mystate: state,
+            presentation: true,
         }
     }
+
+    pub fn presentation(mut self, presentation: bool) -> Self {
+        self.presentation = presentation;
+        self
+    }
 }
 
 impl<T:States+Copy> Plugin for AliensPlugin<T> {
     fn build(&self, app: &mut App) {
+        if self.presentation {
+            app
+                .init_resource::<AliensAssets>()
+                .add_systems(Update, aliens_sprites.run_if(in_state(MainState::Invaders)));
+        }
         app
-            .init_resource::<AliensAssets>()
-
+            .register_type::<MAlienBox>()
+            .register_type::<MAlien>()
             .add_systems(OnEnter(MainState::Invaders), aliens_setup)
-            .add_systems(Update, aliens_move.run_if(in_state(self.mystate)))
-            .add_systems(Update, check_shot.run_if(in_state(self.mystate)))
-            .add_systems(PostUpdate, check_win.run_if(in_state(self.mystate)))
-            .add_systems(PostUpdate, check_lose.run_if(in_state(self.mystate)))
-            .add_systems(PostUpdate, shoot.run_if(in_state(self.mystate)))
+            .add_systems(Update, (save::restore_saved::<MAlienBox>, save::restore_saved::<MAlien>).run_if(in_state(MainState::Invaders)))
+            .add_systems(FixedUpdate, aliens_move.in_set(InvadersSet::Move).run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, check_shot.in_set(InvadersSet::Collide).run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, (check_win, check_lose, shoot).in_set(InvadersSet::Resolve).run_if(in_state(self.mystate)))
 
-            .add_systems(Update, cheat_win.run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, cheat_win.in_set(InvadersSet::Input).run_if(in_state(self.mystate)))
         ;
     }
 }
 
-#[derive(Component, Debug)]
-struct MAlienBox {
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component, Save)]
+pub struct MAlienBox {
     area: Rect,
     last_update: f32,
+    /// March delay of this wave with all aliens alive.
+    delay: f32,
     step: f32,
+    spawned: u32,
+    /// Steps marched so far, picks the animation frame.
+    steps: u32,
+    wave: u32,
 }
 
-#[derive(Component, Debug)]
-struct MAlien;
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component, Save)]
+pub struct MAlien {
+    /// Index into `aliens.kinds` of the config.
+    kind: usize,
+    hp: u32,
+    row: u32,
+}
 
+/// Mesh and material of every alien kind, in config order, and `sprites/<kind>.png` art if there is any.
 #[derive(Resource)]
-struct AliensAssets {
-    alien_mesh: Handle<Mesh>,
-    alien_mat: Handle<ColorMaterial>,
+pub(super) struct AliensAssets {
+    kinds: Vec<(Handle<Mesh>, Handle<ColorMaterial>)>,
+    sprites: Vec<Option<SpriteSheet>>,
+    /// Pixel art by wave and row, made when first needed.
+    generated: HashMap<(u32, u32), SpriteSheet>,
 }
 
+impl AliensAssets {
+    fn bundle(&self, kind: usize) -> (Mesh2dHandle, Handle<ColorMaterial>) {
+        let (mesh, mat) = &self.kinds[kind];
+        (Mesh2dHandle(mesh.clone()), mat.clone())
+    }
+
+    /// Art for an alien: its kind's sprite file, else generated pixel art for `Pixels` kinds.
+    fn sheet(&mut self, alien: &MAlien, wave: u32, config: &InvadersConfig, images: &mut Assets<Image>) -> Option<&mut SpriteSheet> {
+        if self.sprites[alien.kind].is_some() {
+            return self.sprites[alien.kind].as_mut();
+        }
+        let kind = &config.aliens.kinds[alien.kind];
+        if kind.shape != AlienShape::Pixels {
+            return None;
+        }
+        let art = &config.aliens.pixel_art;
+        Some(self.generated.entry((wave, alien.row)).or_insert_with(|| {
+            let (r, g, b) = kind.color;
+            let image = pixel_art::alien_image(pixel_art::art_seed(art.seed, wave, alien.row), art.size as usize, Color::rgb_u8(r, g, b));
+            SpriteSheet::from_image(images.add(image), ALIEN_FRAMES)
+        }))
+    }
+}
+
+const ALIEN_FRAMES: usize = 2;
+
 impl FromWorld for AliensAssets {
     fn from_world(world: &mut World) -> Self {
-        Self {
-            alien_mesh: world.resource_mut::<Assets<Mesh>>().add(Rectangle::new(1.0, 1.0)),
-            alien_mat: world.resource_mut::<Assets<ColorMaterial>>().add(Color::rgb_u8(81, 130, 155)),
-        }
+        let kinds = world.resource::<InvadersConfig>().aliens.kinds.clone();
+        let sprites = kinds.iter()
+            .map(|kind| SpriteSheet::load(world.resource::<AssetServer>(), &format!("sprites/{}.png", kind.name), ALIEN_FRAMES))
+            .collect();
+        let kinds = kinds.iter().map(|kind| {
+            let mesh = match kind.shape {
+                AlienShape::Pixels | AlienShape::Square => Mesh::from(Rectangle::new(1.0, 1.0)),
+                AlienShape::Circle => Mesh::from(Circle::new(0.5)),
+                AlienShape::Diamond => Mesh::from(RegularPolygon::new(0.5, 4)),
+            };
+            let (r, g, b) = kind.color;
+            (
+                world.resource_mut::<Assets<Mesh>>().add(mesh),
+                world.resource_mut::<Assets<ColorMaterial>>().add(Color::rgb_u8(r, g, b)),
+            )
+        }).collect();
+        Self { kinds, sprites, generated: default() }
+    }
+}
+
+impl SaveRestore for MAlienBox {
+    type Assets = AliensAssets;
+}
+
+impl SaveRestore for MAlien {
+    type Assets = AliensAssets;
+
+    fn restore(&self, alien: &mut EntityCommands, assets: &AliensAssets) {
+        alien.insert(assets.bundle(self.kind));
     }
 }
 
 fn aliens_setup (
     mut commands: Commands,
-    assets: Res<AliensAssets>
+    assets: Option<Res<AliensAssets>>,
+    clock: Res<GameClock>,
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
+) {
+    spawn_wave(&mut commands, assets.as_deref(), &clock, &config, &arena, 1);
+}
+
+fn spawn_wave(
+    commands: &mut Commands,
+    assets: Option<&AliensAssets>,
+    clock: &GameClock,
+    config: &InvadersConfig,
+    arena: &Arena,
+    wave: u32,
 ) {
+    let drop = config.waves.drop(wave);
     let alien_area = Rect {
-        min: Vec2::new(-640.0, -100.0),
-        max: Vec2::new(460.0, 400.0),
+        min: Vec2::new(arena.left(), arena.formation_bottom - drop),
+        max: Vec2::new(arena.right() - arena.march_room, arena.formation_top - drop),
     };
-    let inv_size = alien_area.width() / (2 * 11 - 1) as f32;
+    let (rows, columns) = (config.aliens.rows, config.aliens.columns);
+    let inv_size = (alien_area.width() / (2 * columns - 1) as f32).min(alien_area.height() / rows as f32);
+    let row_gap = (alien_area.height() - rows as f32 * inv_size) / 1.max(rows - 1) as f32;
 
     commands.spawn((
         MInvaders,
         MAlienBox {
             area: alien_area,
-            last_update: 0.0,
+            last_update: clock.elapsed,
+            delay: config.aliens.march_delay * config.waves.march_factor(wave),
+            spawned: rows * columns,
+            steps: 0,
+            wave,
             step: inv_size / 3.0,
         },
         Transform::from_xyz(alien_area.center().x, alien_area.center().y, 0.0),
+        Interpolated::at(alien_area.center().extend(0.0)),
         GlobalTransform::IDENTITY,
         InheritedVisibility::default(),
         Visibility::default(),
     )).with_children(|commands| {
-        for y in 0..6 {
-            for x in 0..11 {
-                commands.spawn((
-                    MaterialMesh2dBundle {
-                        mesh: Mesh2dHandle(assets.alien_mesh.clone()),
-                        material: assets.alien_mat.clone(),
-                        transform: Transform::from_xyz(
-                            -alien_area.width() / 2.0 + x as f32 * (2.0*inv_size) + inv_size / 2.0,
-                            alien_area.height() / 2.0 - y as f32 * (inv_size + (alien_area.height() - 6.0*inv_size)/5.0) - inv_size / 2.0,
-                            0.0).with_scale(Vec3::ONE * inv_size),
-                        ..default()
-                    },
-                    MAlien));
+        for y in 0..rows {
+            let kind = config.aliens.kind_index(config.waves.row_kind(wave, y + 1)).unwrap_or(0);
+            for x in 0..columns {
+                let mut alien = commands.spawn((
+                    SpatialBundle::from_transform(Transform::from_xyz(
+                        -alien_area.width() / 2.0 + x as f32 * (2.0*inv_size) + inv_size / 2.0,
+                        alien_area.height() / 2.0 - y as f32 * (inv_size + row_gap) - inv_size / 2.0,
+                        0.0).with_scale(Vec3::ONE * inv_size)),
+                    Hitbox::new(Vec2::splat(inv_size), Layer::Alien),
+                    MAlien { kind, hp: config.aliens.kinds[kind].hp, row: y }));
+                if let Some(assets) = assets {
+                    alien.insert(assets.bundle(kind));
+                }
             }
         }
     });
 }
 
-
 fn aliens_move(
     mut qalien_box: Query<(&mut MAlienBox, &mut Transform, Option<&Children>)>,
     qaliens: Query<&Transform, Without<MAlienBox>>,
-    time: Res<Time>
+    clock: Res<GameClock>,
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
+    mut stepped: EventWriter<FormationStepped>,
 ) {
     let (mut alien_box, mut area, Some(children)) = qalien_box.single_mut() else {
         return;
     };
-    let now = time.elapsed_seconds();
-    if now - alien_box.last_update < 1.0 {
+    let now = clock.elapsed;
+    let delay = alien_box.delay * config.aliens.march_curve.factor(children.len(), alien_box.spawned as usize);
+    if now - alien_box.last_update < delay {
         return;
     }
     alien_box.last_update = now;
@@ -135,50 +248,101 @@ fn aliens_move(
     alien_box.area = area_new;
 
     if alien_box.step > 0.0 {
-        if area_new.max.x < 640.0 - alien_box.step {
+        if area_new.max.x < arena.right() - alien_box.step {
             area.translation.x += alien_box.step;
         } else {
             area.translation.y -= alien_box.step;
             alien_box.step *= -1.0;
         }
     } else {
-        if area_new.min.x > -640.0 - alien_box.step {
+        if area_new.min.x > arena.left() - alien_box.step {
             area.translation.x += alien_box.step;
         } else {
             area.translation.y += alien_box.step;
             alien_box.step *= -1.0;
         }
     }
+    alien_box.steps += 1;
+    stepped.send(FormationStepped { steps: alien_box.steps });
     // println!("new alien area {:?}", area_new);
 }
 
+/// Swaps alien meshes for sprites once the art is loaded and shows the march frame.
+#[allow(clippy::too_many_arguments)]
+fn aliens_sprites(
+    mut commands: Commands,
+    mut assets: ResMut<AliensAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    config: Res<InvadersConfig>,
+    qbox: Query<&MAlienBox>,
+    qmeshes: Query<(Entity, &MAlien, &Parent), With<Mesh2dHandle>>,
+    mut qsprites: Query<(&Parent, &mut TextureAtlas), With<MAlien>>,
+) {
+    for (e, alien, parent) in qmeshes.iter() {
+        let Ok(alien_box) = qbox.get(parent.get()) else {
+            continue;
+        };
+        let Some(sheet) = assets.sheet(alien, alien_box.wave, &config, &mut images) else {
+            continue;
+        };
+        if let Some(sprite) = sheet.sprite(alien_box.steps as usize, Vec2::ONE, &images, &mut layouts) {
+            commands.entity(e).remove::<(Mesh2dHandle, Handle<ColorMaterial>)>().insert(sprite);
+        }
+    }
+    for (parent, mut atlas) in qsprites.iter_mut() {
+        let Ok(alien_box) = qbox.get(parent.get()) else {
+            continue;
+        };
+        let frame = alien_box.steps as usize % ALIEN_FRAMES;
+        if atlas.index != frame {
+            atlas.index = frame;
+        }
+    }
+}
+
 fn check_shot(
     mut commands: Commands,
-    qaliens: Query<(Entity, &Parent, &GlobalTransform), With<MAlien>>,
-    qbullet: Query<(Entity, &Transform), (With<MBullet>, Without<MAlienBullet>)>,
-    mut game: ResMut<InvadersGame>,
+    mut collisions: EventReader<Collision>,
+    qbox: Query<&Transform, With<MAlienBox>>,
+    mut qaliens: Query<(&Parent, &Transform, &mut MAlien)>,
+    config: Res<InvadersConfig>,
+    mut killed: EventWriter<AlienKilled>,
 ) {
-    for (ae, ap, at) in qaliens.iter() {
-        for (be, bt) in qbullet.iter() {
-            let at = at.compute_transform();
-            let abox = Rect::from_center_size(at.translation.xy(), at.scale.xy());
-            if abox.contains(bt.translation.xy()) {
-                commands.entity(ap.get()).remove_children(&[ae]);
-                commands.entity(ae).despawn();
-                commands.entity(be).despawn();
-                game.score += 30;
-            }
+    for c in collisions.read().filter(|c| c.is(Layer::PlayerBullet, Layer::Alien)) {
+        let Ok((ap, at, mut alien)) = qaliens.get_mut(c.b) else {
+            continue;
+        };
+        // already killed by another bullet this tick
+        if alien.hp == 0 {
+            continue;
+        }
+        commands.entity(c.a).despawn();
+        alien.hp -= 1;
+        if alien.hp == 0 {
+            let pos = qbox.get(ap.get()).map_or(Vec3::ZERO, |t| t.translation) + at.translation;
+            commands.entity(ap.get()).remove_children(&[c.b]);
+            commands.entity(c.b).despawn();
+            killed.send(AlienKilled {
+                pos: pos.xy(),
+                points: config.aliens.kinds[alien.kind].points,
+                saucer: false,
+            });
         }
     }
 }
 
 
+/// Kills the wave when `skip_wave` gets pressed, it goes through `PlayerInput` to be replayed.
 fn cheat_win(
-    input: Res<ButtonInput<KeyCode>>,
+    input: Res<PlayerInput>,
+    mut held: Local<bool>,
     mut commands: Commands,
     qaliens: Query<(Entity, &Parent), With<MAlien>>
 ) {
-    if input.just_pressed(KeyCode::KeyZ) && input.any_pressed([KeyCode::AltRight, KeyCode::AltLeft]) {
+    let pressed = input.skip_wave && !*held;
+    *held = input.skip_wave;
+    if pressed {
         for (e, p) in qaliens.iter() {
             commands.entity(p.get()).remove_children(&[e]);
             commands.entity(e).despawn();
@@ -186,54 +350,74 @@ fn cheat_win(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_win(
+    mut commands: Commands,
     qaliens: Query<&MAlien>,
-    // mut game: ResMut<InvadersGame>,
+    qbox: Query<Entity, With<MAlienBox>>,
+    mut game: ResMut<InvadersGame>,
+    assets: Option<Res<AliensAssets>>,
+    clock: Res<GameClock>,
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
     mut state: ResMut<NextState<InvaderState>>,
+    mut cleared: EventWriter<WaveCleared>,
 ) {
     if !qaliens.is_empty() {
         return;
     }
-    // game.win = true;
-    state.set(InvaderState::Win);
+    cleared.send(WaveCleared { wave: game.wave });
+    if config.waves.count != 0 && game.wave >= config.waves.count {
+        state.set(InvaderState::Win);
+        return;
+    }
+    for e in qbox.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    game.wave += 1;
+    spawn_wave(&mut commands, assets.as_deref(), &clock, &config, &arena, game.wave);
 }
 
 fn check_lose(
     alien_box: Query<&MAlienBox>,
     // mut game: ResMut<InvadersGame>,
+    arena: Res<Arena>,
     mut state: ResMut<NextState<InvaderState>>,
 ) {
     let alien_box = alien_box.single();
-    if alien_box.area.min.y <= -350.0 {
+    if alien_box.area.min.y <= arena.lose_line() {
         state.set(InvaderState::Gameover);
     }
 }
 
-fn shoot(
+#[allow(clippy::too_many_arguments)]
+pub fn shoot(
     qbox: Query<(Entity, Option<&Children>, &Transform), With<MAlienBox>>,
     qalien: Query<&Transform, With<MAlien>>,
-    game: Res<InvadersGame>,
+    mut game: ResMut<InvadersGame>,
+    config: Res<InvadersConfig>,
+    mut rng: ResMut<InvadersRng>,
     mut event: EventWriter<ShootEvent>,
-    time: Res<Time>,
-    mut last_upd: Local<f32>,
+    clock: Res<GameClock>,
     qbat: Query<&Transform, With<MBat>>,
 ) {
-    let now = time.elapsed_seconds_wrapped();
-    let delta = now - *last_upd;
-    if delta < 9.0 / (3.0 + game.time / 10.0) {
+    let now = clock.elapsed;
+    let delta = now - game.last_alien_shot;
+    if delta < config.aliens.fire_delay.delay(game.time) * config.waves.fire_factor(game.wave) {
         return;
     }
-    *last_upd = now;
+    game.last_alien_shot = now;
 
     let (_e, Some(_children), alien_box) = qbox.single() else {
         return;
     };
-    let mut rng = rand::thread_rng();
-    if rng.gen::<f32>() > 0.5 {
+    if rng.gen::<f32>() < config.aliens.fire_chance {
         let bat = qbat.single();
-        let shooters = qalien.iter()
-            .filter(|t| (t.translation.x + alien_box.translation.x - bat.translation.x).abs() < 200.0)
+        let mut shooters = qalien.iter()
+            .filter(|t| (t.translation.x + alien_box.translation.x - bat.translation.x).abs() < config.aliens.aim_range)
             .collect::<Vec<_>>();
+        // the query order changes when a saved game is loaded, the pick must not
+        shooters.sort_by(|a, b| a.translation.x.total_cmp(&b.translation.x).then(a.translation.y.total_cmp(&b.translation.y)));
         // println!("shooters {}", shooters.len());
         if shooters.is_empty() {
             return;
@@ -244,7 +428,7 @@ fn shoot(
         // };
         event.send(ShootEvent::new(
             (alien.translation + alien_box.translation + Vec3::NEG_Y * alien.scale / 2.0).xy(),
-            Vec2::new(0.0, -150.0),
+            Vec2::new(0.0, -config.aliens.bullet_speed),
             true
         ));
         // println!("shoot! {:?}", &children.len());
@@ -1,53 +1,101 @@
-use bevy::app::{App, Plugin, Update};
+use bevy::app::{App, Plugin};
 use bevy::asset::{Assets, Handle};
-use bevy::input::ButtonInput;
 use bevy::prelude::*;
-use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::ecs::system::EntityCommands;
+use bevy::sprite::Mesh2dHandle;
 
-use crate::invaders::{InvadersGame, InvaderState, MInvaders};
+use crate::invaders::config::{Arena, BatConfig, InvadersConfig};
+use crate::invaders::{score_events, GameClock, InvadersGame, InvadersSet, InvaderState, MInvaders};
+use crate::invaders::collision::{Collision, Hitbox, Layer};
+use crate::invaders::events::PlayerHit;
+use crate::invaders::input::PlayerInput;
+use crate::invaders::interpolation::Interpolated;
 use crate::invaders::bullet::{MBullet, ShootEvent};
+use crate::invaders::save::{self, ReflectSave, SaveRestore};
+use crate::invaders::sprite::SpriteSheet;
 use crate::MainState;
 
 pub struct BatPlugin<T: States+Copy> {
     mystate: T,
+    presentation: bool,
 }
 
 impl<T:States+Copy> BatPlugin<T> {
     pub fn for_state(state: T) -> Self {
         Self {
             mystate: state,
+            presentation: true,
         }
     }
+
+    pub fn presentation(mut self, presentation: bool) -> Self {
+        self.presentation = presentation;
+        self
+    }
 }
 
 impl<T:States+Copy> Plugin for BatPlugin<T> {
     fn build(&self, app: &mut App) {
+        if self.presentation {
+            app
+                .init_resource::<BatAssets>()
+                .add_systems(Update, bat_sprite.run_if(in_state(MainState::Invaders)));
+        }
         app
-            .init_resource::<BatAssets>()
-
+            .register_type::<MBat>()
             .add_systems(OnEnter(MainState::Invaders), bat_setup)
+            .add_systems(Update, save::restore_saved::<MBat>.run_if(in_state(MainState::Invaders)))
 
-            .add_systems(PreUpdate, bat_key_input.run_if(in_state(self.mystate)))
-            .add_systems(PreUpdate, bat_shoot.run_if(in_state(InvaderState::Game)))
-            .add_systems(Update, bat_update.run_if(in_state(self.mystate)))
-            .add_systems(Update, check_shot.run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, (bat_key_input, bat_shoot).in_set(InvadersSet::Input).run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, (bat_respawn, bat_update).chain().in_set(InvadersSet::Move).run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, check_shot.in_set(InvadersSet::Collide).run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, (lose_life, extra_life.after(score_events)).in_set(InvadersSet::Resolve).run_if(in_state(self.mystate)))
+            .add_systems(Update, bat_blink.run_if(in_state(MainState::Invaders)))
         ;
     }
 }
 
 
-#[derive(Component, Default, Debug)]
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component, Save)]
 pub struct MBat {
     mass: f32,
     v: f32,
     f: f32,
     last_shoot: f32,
+    respawn_at: Option<f32>,
+    invulnerable_until: f32,
+}
+
+/// Drawn and hit size of the bat.
+const BAT_SIZE: Vec2 = Vec2::new(80.0, 20.0);
+
+/// Speed wanted per unit of distance to a chased target, in 1/s.
+const CHASE_GAIN: f32 = 3.0;
+/// Seconds to reach the wanted speed, when the force allows.
+const CHASE_TIME: f32 = 0.2;
+
+impl MBat {
+    fn is_down(&self) -> bool {
+        self.respawn_at.is_some()
+    }
+
+    /// Steering, from -1 to 1, that takes the bat from `x` to `target_x` and stops it there.
+    ///
+    /// Only picks the force, `bat_update` moves the bat like for any other input.
+    pub fn chase(&self, x: f32, target_x: f32, config: &BatConfig) -> f32 {
+        let max_speed = config.force / config.drag;
+        let speed = ((target_x - x) * CHASE_GAIN).clamp(-max_speed, max_speed);
+        let f = speed * config.drag + self.mass * (speed - self.v) / CHASE_TIME;
+        (f / config.force).clamp(-1.0, 1.0)
+    }
 }
 
 #[derive(Resource)]
-struct BatAssets {
+pub(super) struct BatAssets {
     bat_mesh: Handle<Mesh>,
     bat_mat: Handle<ColorMaterial>,
+    sprite: Option<SpriteSheet>,
 }
 
 impl FromWorld for BatAssets {
@@ -55,41 +103,59 @@ impl FromWorld for BatAssets {
         Self {
             bat_mesh: world.resource_mut::<Assets<Mesh>>().add(Rectangle::new(1.0, 1.0)),
             bat_mat: world.resource_mut::<Assets<ColorMaterial>>().add(Color::rgb_u8(246, 153, 92)),
+            sprite: SpriteSheet::load(world.resource::<AssetServer>(), "sprites/bat.png", 1),
         }
     }
 }
 
+impl SaveRestore for MBat {
+    type Assets = BatAssets;
+
+    fn restore(&self, bat: &mut EntityCommands, assets: &BatAssets) {
+        bat.insert((Mesh2dHandle(assets.bat_mesh.clone()), assets.bat_mat.clone()));
+    }
+}
+
 fn bat_setup(
     mut commands: Commands,
-    assets: Res<BatAssets>
+    assets: Option<Res<BatAssets>>,
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
 ) {
-    commands.spawn((
-        MaterialMesh2dBundle {
-            mesh: Mesh2dHandle(assets.bat_mesh.clone()),
-            material: assets.bat_mat.clone(),
-            transform: Transform::from_xyz(0.0, -360.0, -1.0).with_scale([80.0, 20.0, 1.0].into()),
-            ..default()
-        },
+    let pos = Vec3::new(0.0, arena.bat_height, -1.0);
+    let mut bat = commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(pos).with_scale(BAT_SIZE.extend(1.0))),
+        Interpolated::at(pos),
+        Hitbox::new(BAT_SIZE, Layer::Bat),
         MInvaders,
         MBat {
-            mass: 1.0,
+            mass: config.bat.mass,
+            // ready to fire from the first tick, the game clock starts at 0
+            last_shoot: -config.bat.shoot_delay,
             ..default()
         }));
+    if let Some(assets) = assets {
+        bat.insert((Mesh2dHandle(assets.bat_mesh.clone()), assets.bat_mat.clone()));
+    }
 }
 
 fn bat_update(
     mut qbat: Query<(&mut MBat, &mut Transform)>,
-    game: Res<InvadersGame>,
-    time: Res<Time>
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
+    clock: Res<GameClock>
 ) {
     let (mut bat, mut transform) = qbat.single_mut();
+    if bat.is_down() {
+        return;
+    }
 
-    let delta = time.delta_seconds();
-    bat.v += delta * (bat.f - bat.v * game.drag) / bat.mass;
+    let delta = clock.delta;
+    bat.v += delta * (bat.f - bat.v * config.bat.drag) / bat.mass;
     transform.translation.x += delta * bat.v;
-    // println!("after transform: {:?} f {},dd {:?} {:?}", bat, bat.f - bat.v * game.drag, transform, game);
+    // println!("after transform: {:?} f {},dd {:?} {:?}", bat, bat.f - bat.v * config.bat.drag, transform, config);
 
-    let x_range = (-640.0 + transform.scale.x / 2.0)..=(640.0 - transform.scale.x / 2.0);
+    let x_range = (arena.left() + BAT_SIZE.x / 2.0)..=(arena.right() - BAT_SIZE.x / 2.0);
     if !x_range.contains(&transform.translation.x) {
         transform.translation.x = transform.translation.x.clamp(*x_range.start(), *x_range.end());
         bat.v = - bat.v * 0.7;
@@ -97,50 +163,127 @@ fn bat_update(
 }
 
 fn bat_key_input(
-    input: Res<ButtonInput<KeyCode>>,
+    input: Res<PlayerInput>,
     mut qbat: Query<&mut MBat>,
-    game: Res<InvadersGame>,
+    config: Res<InvadersConfig>,
 ) {
-    let mut new_f = 0.0;
-    if input.pressed(KeyCode::KeyD) {
-        new_f += game.f;
-    }
-    if input.pressed(KeyCode::KeyA) {
-        new_f -= game.f;
-    }
-    qbat.single_mut().f = new_f;
+    let new_f = config.bat.force * input.steering();
+    let mut bat = qbat.single_mut();
+    bat.f = if bat.is_down() { 0.0 } else { new_f };
 }
 
 fn bat_shoot (
     mut event: EventWriter<ShootEvent>,
-    input: Res<ButtonInput<MouseButton>>,
+    input: Res<PlayerInput>,
     mut qbat: Query<(&mut MBat, &Transform)>,
-    time: Res<Time>,
-    mut game: ResMut<InvadersGame>,
+    clock: Res<GameClock>,
+    config: Res<InvadersConfig>,
 ) {
     let (mut bat, t) = qbat.single_mut();
-    if input.pressed(MouseButton::Left) {
-        let now = time.elapsed_seconds_wrapped();
-        if now - bat.last_shoot >= game.shoot_delay {
-            event.send(ShootEvent::new((t.translation + Vec3::Y * 20.0).xy(), Vec2::new(0.0, 100.0), false));
+    if input.fire && !bat.is_down() {
+        let now = clock.elapsed;
+        if now - bat.last_shoot >= config.bat.shoot_delay {
+            event.send(ShootEvent::new((t.translation + Vec3::Y * 20.0).xy(), Vec2::new(0.0, config.bat.bullet_speed), false));
             bat.last_shoot = now;
-            game.score = 0.max(game.score - 10);
         }
     }
 }
 
 fn check_shot(
     mut commands: Commands,
-    qbat: Query<(&MBat, &Transform), With<MBat>>,
-    qbullet: Query<(Entity, &Transform), With<MBullet>>,
+    mut collisions: EventReader<Collision>,
+    mut qbat: Query<&mut MBat>,
+    qbullet: Query<&Transform, With<MBullet>>,
+    clock: Res<GameClock>,
+    config: Res<InvadersConfig>,
+    mut hit: EventWriter<PlayerHit>,
+) {
+    let mut bat = qbat.single_mut();
+    for c in collisions.read().filter(|c| c.is(Layer::AlienBullet, Layer::Bat)) {
+        if bat.is_down() || clock.elapsed < bat.invulnerable_until {
+            continue;
+        }
+        if let Ok(t) = qbullet.get(c.a) {
+            hit.send(PlayerHit { pos: t.translation.xy() });
+        }
+        commands.entity(c.a).despawn();
+        bat.respawn_at = Some(clock.elapsed + config.lives.respawn_delay);
+        bat.v = 0.0;
+    }
+}
+
+fn bat_respawn(
+    mut qbat: Query<(&mut MBat, &mut Transform, &mut Interpolated)>,
+    clock: Res<GameClock>,
+    config: Res<InvadersConfig>,
+) {
+    let (mut bat, mut transform, mut interpolated) = qbat.single_mut();
+    let Some(respawn_at) = bat.respawn_at else {
+        return;
+    };
+    if clock.elapsed >= respawn_at {
+        transform.translation.x = 0.0;
+        interpolated.reset(transform.translation);
+        bat.respawn_at = None;
+        bat.invulnerable_until = clock.elapsed + config.lives.invulnerable;
+    }
+}
+
+fn lose_life(
+    mut hit: EventReader<PlayerHit>,
+    mut game: ResMut<InvadersGame>,
     mut state: ResMut<NextState<InvaderState>>,
 ) {
-    let (_bat, tbat) = qbat.single();
-    for (e, t) in qbullet.iter() {
-        let bat_box = Rect::from_center_size(tbat.translation.xy(), tbat.scale.xy());
-        if bat_box.contains(t.translation.xy()) {
-            commands.entity(e).despawn();
+    for _ in hit.read() {
+        game.lives = game.lives.saturating_sub(1);
+        if game.lives == 0 {
             state.set(InvaderState::Gameover);
         }
     }
+}
+
+fn extra_life(
+    mut game: ResMut<InvadersGame>,
+    config: Res<InvadersConfig>,
+) {
+    while let Some(&threshold) = config.lives.extra_life_scores.get(game.extra_lives) {
+        if game.score < threshold {
+            break;
+        }
+        game.extra_lives += 1;
+        game.lives += 1;
+    }
+}
+
+/// Swaps the bat mesh for `sprites/bat.png` once it is loaded.
+fn bat_sprite(
+    mut commands: Commands,
+    mut assets: ResMut<BatAssets>,
+    images: Res<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    qbat: Query<Entity, (With<MBat>, With<Mesh2dHandle>)>,
+) {
+    let Some(sheet) = assets.sprite.as_mut() else {
+        return;
+    };
+    for e in qbat.iter() {
+        if let Some(sprite) = sheet.sprite(0, Vec2::ONE, &images, &mut layouts) {
+            commands.entity(e).remove::<(Mesh2dHandle, Handle<ColorMaterial>)>().insert(sprite);
+        }
+    }
+}
+
+fn bat_blink(
+    mut qbat: Query<(&MBat, &mut Visibility)>,
+    clock: Res<GameClock>,
+) {
+    let Ok((bat, mut visibility)) = qbat.get_single_mut() else {
+        return;
+    };
+    let blink_off = clock.elapsed < bat.invulnerable_until && (clock.elapsed * 8.0) as i32 % 2 == 0;
+    *visibility = if bat.is_down() || blink_off {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
 }
\ No newline at end of file
@@ -1,30 +1,47 @@
 use bevy::prelude::*;
-use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::ecs::system::EntityCommands;
+use bevy::sprite::Mesh2dHandle;
 
-use crate::invaders::{InvaderState, MInvaders};
+use crate::invaders::{GameClock, InvadersSet, MInvaders};
+use crate::invaders::collision::{Hitbox, Layer};
+use crate::invaders::config::Arena;
+use crate::invaders::events::ShotFired;
+use crate::invaders::interpolation::Interpolated;
+use crate::invaders::save::{self, ReflectSave, SaveRestore};
+use crate::invaders::sprite::SpriteSheet;
 
-pub struct BulletPlugin;
+pub struct BulletPlugin {
+    pub presentation: bool,
+}
 
 impl Plugin for BulletPlugin {
     fn build(&self, app: &mut App) {
+        if self.presentation {
+            app
+                .init_resource::<BulletAssets>()
+                .add_systems(Update, bullet_sprites);
+        }
         app
             .add_event::<ShootEvent>()
-            .init_resource::<BulletAssets>()
+            .register_type::<MBullet>()
+            .register_type::<MAlienBullet>()
 
-            .add_systems(Update, bullet_move.run_if(in_state(InvaderState::Game)))
-            .add_systems(Update, bullet_cleanup.run_if(in_state(InvaderState::Game)))
-            .add_systems(Update, spawn_bullet.run_if(in_state(InvaderState::Game)))
+            .add_systems(FixedUpdate, (spawn_bullet, bullet_move).chain().in_set(InvadersSet::Move))
+            .add_systems(FixedUpdate, bullet_cleanup.in_set(InvadersSet::Resolve))
+            .add_systems(Update, save::restore_saved::<MBullet>)
         ;
     }
 }
 
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Save)]
 pub struct MBullet {
     v: Vec2,
 }
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Save)]
 pub struct MAlienBullet;
 
 #[derive(Event)]
@@ -41,9 +58,10 @@ impl ShootEvent {
 }
 
 #[derive(Resource)]
-struct BulletAssets {
+pub(super) struct BulletAssets {
     mesh: Handle<Mesh>,
     mat: Handle<ColorMaterial>,
+    sprite: Option<SpriteSheet>,
 }
 
 impl FromWorld for BulletAssets {
@@ -51,15 +69,24 @@ impl FromWorld for BulletAssets {
         Self {
             mesh: world.resource_mut::<Assets<Mesh>>().add(Circle::new(3.0)),
             mat: world.resource_mut::<Assets<ColorMaterial>>().add(Color::rgb_u8(155, 176, 193)),
+            sprite: SpriteSheet::load(world.resource::<AssetServer>(), "sprites/bullet.png", 1),
         }
     }
 }
 
+impl SaveRestore for MBullet {
+    type Assets = BulletAssets;
+
+    fn restore(&self, bullet: &mut EntityCommands, assets: &BulletAssets) {
+        bullet.insert((Mesh2dHandle(assets.mesh.clone()), assets.mat.clone()));
+    }
+}
+
 fn bullet_move (
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut qbullets: Query<(&MBullet, &mut Transform)>
 ) {
-    let delta = time.delta_seconds();
+    let delta = clock.delta;
     for (b, mut t) in qbullets.iter_mut() {
         t.translation.x += b.v.x * delta;
         t.translation.y += b.v.y * delta;
@@ -68,27 +95,53 @@ fn bullet_move (
 
 fn bullet_cleanup(
     mut commands: Commands,
-    qbullets: Query<(Entity, &ViewVisibility), With<MBullet>>
+    qbullets: Query<(Entity, &Transform), With<MBullet>>,
+    arena: Res<Arena>,
 ) {
-    for (e, vis) in qbullets.iter() {
-        if !vis.get() {
+    let field = arena.rect();
+    for (e, t) in qbullets.iter() {
+        if !field.contains(t.translation.xy()) {
             commands.entity(e).despawn_recursive();
         }
     }
 }
 
-fn spawn_bullet(mut event: EventReader<ShootEvent>, mut commands: Commands, assets: Res<BulletAssets>) {
+/// Swaps bullet meshes for `sprites/bullet.png` once it is loaded.
+fn bullet_sprites(
+    mut commands: Commands,
+    mut assets: ResMut<BulletAssets>,
+    images: Res<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    qbullets: Query<Entity, (With<MBullet>, With<Mesh2dHandle>)>,
+) {
+    let Some(sheet) = assets.sprite.as_mut() else {
+        return;
+    };
+    for e in qbullets.iter() {
+        if let Some(sprite) = sheet.sprite(0, Vec2::splat(6.0), &images, &mut layouts) {
+            commands.entity(e).remove::<(Mesh2dHandle, Handle<ColorMaterial>)>().insert(sprite);
+        }
+    }
+}
+
+fn spawn_bullet(
+    mut event: EventReader<ShootEvent>,
+    mut commands: Commands,
+    assets: Option<Res<BulletAssets>>,
+    mut fired: EventWriter<ShotFired>,
+) {
     for e in event.read() {
+        fired.send(ShotFired { pos: e.pos, alien: e.alien });
         let mut bullet = commands.spawn((
-            MaterialMesh2dBundle {
-                mesh: Mesh2dHandle(assets.mesh.clone()),
-                material: assets.mat.clone(),
-                transform: Transform::from_xyz(e.pos.x, e.pos.y, 0.0),
-                ..default()
-            },
+            SpatialBundle::from_transform(Transform::from_xyz(e.pos.x, e.pos.y, 0.0)),
+            Interpolated::at(e.pos.extend(0.0)),
             MBullet { v: e.v },
+            Hitbox::new(Vec2::ZERO, if e.alien { Layer::AlienBullet } else { Layer::PlayerBullet }),
             MInvaders,
         ));
+        if let Some(assets) = &assets {
+            bullet.insert((Mesh2dHandle(assets.mesh.clone()), assets.mat.clone()));
+        }
         if e.alien {
             bullet.insert(MAlienBullet);
         }
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use bevy::ecs::system::EntityCommands;
+use bevy::sprite::Mesh2dHandle;
+
+use crate::invaders::config::{Arena, InvadersConfig};
+use crate::invaders::{InvadersSet, MInvaders};
+use crate::invaders::collision::{Collision, Hitbox, Layer};
+use crate::invaders::save::{self, ReflectSave, SaveRestore};
+use crate::MainState;
+
+/// Shields between the bat and the formation, worn away cell by cell.
+pub struct BunkerPlugin<T: States+Copy> {
+    mystate: T,
+    presentation: bool,
+}
+
+impl<T:States+Copy> BunkerPlugin<T> {
+    pub fn for_state(state: T) -> Self {
+        Self {
+            mystate: state,
+            presentation: true,
+        }
+    }
+
+    pub fn presentation(mut self, presentation: bool) -> Self {
+        self.presentation = presentation;
+        self
+    }
+}
+
+impl<T:States+Copy> Plugin for BunkerPlugin<T> {
+    fn build(&self, app: &mut App) {
+        if self.presentation {
+            app.init_resource::<BunkerAssets>();
+        }
+        app
+            .register_type::<MBunker>()
+            .register_type::<MBunkerCell>()
+            .add_systems(OnEnter(MainState::Invaders), bunkers_setup)
+            .add_systems(FixedUpdate, check_hits.in_set(InvadersSet::Collide).run_if(in_state(self.mystate)))
+            .add_systems(Update, (save::restore_saved::<MBunker>, save::restore_saved::<MBunkerCell>).run_if(in_state(MainState::Invaders)))
+        ;
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component, Save)]
+struct MBunker;
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component, Save)]
+struct MBunkerCell;
+
+#[derive(Resource)]
+pub(super) struct BunkerAssets {
+    mesh: Handle<Mesh>,
+    mat: Handle<ColorMaterial>,
+}
+
+impl FromWorld for BunkerAssets {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            mesh: world.resource_mut::<Assets<Mesh>>().add(Rectangle::new(1.0, 1.0)),
+            mat: world.resource_mut::<Assets<ColorMaterial>>().add(Color::rgb_u8(129, 178, 124)),
+        }
+    }
+}
+
+impl SaveRestore for MBunker {
+    type Assets = BunkerAssets;
+}
+
+impl SaveRestore for MBunkerCell {
+    type Assets = BunkerAssets;
+
+    fn restore(&self, cell: &mut EntityCommands, assets: &BunkerAssets) {
+        cell.insert((Mesh2dHandle(assets.mesh.clone()), assets.mat.clone()));
+    }
+}
+
+fn bunkers_setup(
+    mut commands: Commands,
+    assets: Option<Res<BunkerAssets>>,
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
+) {
+    let config = &config.bunkers;
+    let cell = config.cell_size;
+    let columns = config.shape.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    let size = Vec2::new(columns as f32, config.shape.len() as f32) * cell;
+    let spacing = arena.width / config.count as f32;
+    for i in 0..config.count {
+        let x = arena.left() + (i as f32 + 0.5) * spacing;
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(x, arena.bunkers_height(), 0.0)),
+            MInvaders,
+            MBunker,
+        )).with_children(|commands| {
+            for (y, row) in config.shape.iter().enumerate() {
+                for (x, _) in row.chars().enumerate().filter(|(_, c)| *c == '#') {
+                    let mut cell = commands.spawn((
+                        SpatialBundle::from_transform(Transform::from_xyz(
+                            -size.x / 2.0 + (x as f32 + 0.5) * cell,
+                            size.y / 2.0 - (y as f32 + 0.5) * cell,
+                            0.0).with_scale(Vec3::new(cell, cell, 1.0))),
+                        Hitbox::new(Vec2::splat(cell), Layer::Shield),
+                        MBunkerCell,
+                    ));
+                    if let Some(assets) = &assets {
+                        cell.insert((Mesh2dHandle(assets.mesh.clone()), assets.mat.clone()));
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Every bullet, the player's and the aliens', takes away the cell it hits,
+/// aliens marching into a bunker wipe out the cells they touch.
+fn check_hits(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+) {
+    let mut removed = Vec::new();
+    for c in collisions.read().filter(|c| c.b_layer == Layer::Shield) {
+        // a bullet stops even when another one took the cell in the same tick
+        if c.a_layer != Layer::Alien {
+            commands.entity(c.a).despawn();
+        }
+        if removed.contains(&c.b) {
+            continue;
+        }
+        removed.push(c.b);
+        commands.entity(c.b).despawn_recursive();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::invaders::InvadersSet;
+use crate::invaders::save::ReflectSave;
+
+/// Finds overlapping hitboxes once per tick and sends one `Collision` for each pair.
+///
+/// Hitboxes are hashed into a grid of `CELL_SIZE` cells, so only boxes sharing a cell are compared.
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<Collision>()
+            .register_type::<Hitbox>()
+            .register_type::<Layer>()
+            .add_systems(FixedUpdate, detect_collisions.in_set(InvadersSet::Detect))
+        ;
+    }
+}
+
+const CELL_SIZE: f32 = 64.0;
+
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Layer {
+    PlayerBullet,
+    AlienBullet,
+    /// Formation aliens and the saucer.
+    Alien,
+    Bat,
+    Shield,
+}
+
+impl Layer {
+    /// Whether `self` collides with `other`, `self` being `a` of the `Collision`.
+    fn hits(self, other: Layer) -> bool {
+        matches!((self, other),
+            (Layer::PlayerBullet, Layer::Alien | Layer::Shield)
+            | (Layer::AlienBullet, Layer::Bat | Layer::Shield)
+            | (Layer::Alien, Layer::Shield))
+    }
+
+    /// Bullets stop at the first thing they hit.
+    fn single_hit(self) -> bool {
+        matches!(self, Layer::PlayerBullet | Layer::AlienBullet)
+    }
+}
+
+/// Collision rectangle centred on the entity, in world units, and what it collides as.
+///
+/// Children are placed relative to their parent, one level deep like the formation and the bunkers.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component, Save)]
+pub struct Hitbox {
+    pub size: Vec2,
+    pub layer: Layer,
+}
+
+impl Hitbox {
+    pub fn new(size: Vec2, layer: Layer) -> Self {
+        Self { size, layer }
+    }
+}
+
+/// `a` ran into `b`, sent once per pair and tick.
+#[derive(Event, Debug)]
+pub struct Collision {
+    pub a: Entity,
+    pub a_layer: Layer,
+    pub b: Entity,
+    pub b_layer: Layer,
+}
+
+impl Collision {
+    pub fn is(&self, a: Layer, b: Layer) -> bool {
+        self.a_layer == a && self.b_layer == b
+    }
+}
+
+fn detect_collisions(
+    q: Query<(Entity, &Hitbox, &Transform, Option<&Parent>)>,
+    qparent: Query<&Transform>,
+    mut events: EventWriter<Collision>,
+) {
+    let boxes = q.iter().map(|(e, hitbox, t, parent)| {
+        let offset = parent.and_then(|p| qparent.get(p.get()).ok()).map_or(Vec2::ZERO, |pt| pt.translation.xy());
+        (e, hitbox.layer, Rect::from_center_size(t.translation.xy() + offset, hitbox.size))
+    }).collect::<Vec<_>>();
+    events.send_batch(find_collisions(&boxes));
+}
+
+fn find_collisions(boxes: &[(Entity, Layer, Rect)]) -> Vec<Collision> {
+    let mut grid = HashMap::<IVec2, Vec<usize>>::new();
+    for (i, (_, _, rect)) in boxes.iter().enumerate() {
+        let (min, max) = (cell(rect.min), cell(rect.max));
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                grid.entry(IVec2::new(x, y)).or_default().push(i);
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for members in grid.values() {
+        for (n, &i) in members.iter().enumerate() {
+            for &j in &members[n + 1..] {
+                let (i, j) = if boxes[i].1.hits(boxes[j].1) {
+                    (i, j)
+                } else if boxes[j].1.hits(boxes[i].1) {
+                    (j, i)
+                } else {
+                    continue;
+                };
+                if overlaps(boxes[i].2, boxes[j].2) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+    }
+    // boxes spanning several cells meet more than once
+    pairs.sort_unstable();
+    pairs.dedup();
+    // a bullet hits the nearest box, not the first one of the query, its order changes when a game is loaded
+    let distance = |(i, j): (usize, usize)| boxes[i].2.center().distance_squared(boxes[j].2.center());
+    pairs.sort_by(|&p, &q| p.0.cmp(&q.0).then(distance(p).total_cmp(&distance(q))));
+
+    let mut spent = HashSet::new();
+    pairs.into_iter().filter_map(|(i, j)| {
+        let ((a, a_layer, _), (b, b_layer, _)) = (boxes[i], boxes[j]);
+        if a_layer.single_hit() && !spent.insert(a) {
+            return None;
+        }
+        Some(Collision { a, a_layer, b, b_layer })
+    }).collect()
+}
+
+fn cell(pos: Vec2) -> IVec2 {
+    (pos / CELL_SIZE).floor().as_ivec2()
+}
+
+/// Like `Rect::intersect` but counting touching edges, so zero sized boxes (bullets) can hit.
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.min.cmple(b.max).all() && b.min.cmple(a.max).all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYERS: [Layer; 5] = [Layer::PlayerBullet, Layer::AlienBullet, Layer::Alien, Layer::Bat, Layer::Shield];
+
+    fn hitbox(id: u32, layer: Layer, center: Vec2, size: Vec2) -> (Entity, Layer, Rect) {
+        (Entity::from_raw(id), layer, Rect::from_center_size(center, size))
+    }
+
+    #[test]
+    fn layer_matrix() {
+        let hitting = [
+            (Layer::PlayerBullet, Layer::Alien),
+            (Layer::PlayerBullet, Layer::Shield),
+            (Layer::AlienBullet, Layer::Bat),
+            (Layer::AlienBullet, Layer::Shield),
+            (Layer::Alien, Layer::Shield),
+        ];
+        for a in LAYERS {
+            for b in LAYERS {
+                assert_eq!(a.hits(b), hitting.contains(&(a, b)), "{:?} hits {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn only_hitting_layers_collide() {
+        for a in LAYERS {
+            for b in LAYERS {
+                let boxes = [hitbox(0, a, Vec2::ZERO, Vec2::splat(10.0)), hitbox(1, b, Vec2::ZERO, Vec2::splat(10.0))];
+                let expected = if a.hits(b) || b.hits(a) { 1 } else { 0 };
+                assert_eq!(find_collisions(&boxes).len(), expected, "{:?} and {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn bullet_over_two_cells_and_two_aliens_hits_once() {
+        // the bullet straddles the cell border at x = 64, each alien sits in one of the cells
+        let boxes = [
+            hitbox(0, Layer::Alien, Vec2::new(54.0, 10.0), Vec2::splat(20.0)),
+            hitbox(1, Layer::PlayerBullet, Vec2::new(64.0, 10.0), Vec2::new(4.0, 16.0)),
+            hitbox(2, Layer::Alien, Vec2::new(72.0, 10.0), Vec2::splat(20.0)),
+        ];
+        assert_ne!(cell(boxes[1].2.min), cell(boxes[1].2.max));
+        let collisions = find_collisions(&boxes);
+        assert_eq!(collisions.len(), 1);
+        let hit = &collisions[0];
+        assert!(hit.is(Layer::PlayerBullet, Layer::Alien));
+        assert_eq!(hit.a, Entity::from_raw(1));
+        // the nearer alien, wherever it is in the query
+        assert_eq!(hit.b, Entity::from_raw(2));
+    }
+
+    #[test]
+    fn alien_over_two_cells_hits_every_shield_once() {
+        let boxes = [
+            hitbox(0, Layer::Alien, Vec2::new(64.0, 64.0), Vec2::splat(40.0)),
+            hitbox(1, Layer::Shield, Vec2::new(50.0, 50.0), Vec2::splat(8.0)),
+            hitbox(2, Layer::Shield, Vec2::new(78.0, 78.0), Vec2::splat(8.0)),
+        ];
+        let mut shields = find_collisions(&boxes).iter().map(|c| c.b).collect::<Vec<_>>();
+        shields.sort();
+        assert_eq!(shields, [Entity::from_raw(1), Entity::from_raw(2)]);
+    }
+
+    #[test]
+    fn zero_sized_bullet_hits_on_the_edge() {
+        let boxes = [
+            hitbox(0, Layer::AlienBullet, Vec2::new(10.0, 0.0), Vec2::ZERO),
+            hitbox(1, Layer::Bat, Vec2::ZERO, Vec2::new(20.0, 10.0)),
+        ];
+        assert_eq!(find_collisions(&boxes).len(), 1);
+    }
+}
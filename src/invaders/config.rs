@@ -0,0 +1,589 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Default location of the tuning file, relative to the assets folder.
+pub const CONFIG_FILE: &str = "invaders.ron";
+
+/// All gameplay tuning values, loaded from `assets/invaders.ron` at startup.
+///
+/// Every field has a default, so the file only needs to list what it changes.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct InvadersConfig {
+    pub arena: Arena,
+    pub bat: BatConfig,
+    pub aliens: AliensConfig,
+    pub score: ScoreConfig,
+    pub waves: WavesConfig,
+    pub lives: LivesConfig,
+    pub saucer: SaucerConfig,
+    pub bunkers: BunkersConfig,
+}
+
+/// The play field, centred on the origin. Inserted as a resource of its own for the systems to read.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+    pub bat_height: f32,
+    /// Top and bottom of the formation of the first wave.
+    pub formation_top: f32,
+    pub formation_bottom: f32,
+    /// Room the formation leaves on the right to march into.
+    pub march_room: f32,
+    /// How far the saucer flies above `formation_top`.
+    pub saucer_above: f32,
+    /// How far the bunker centers are above `bat_height`.
+    pub bunkers_above: f32,
+    /// How far the lose line is above `bat_height`, the game is lost when the formation reaches it.
+    pub lose_above: f32,
+}
+
+impl Arena {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(Vec2::ZERO, self.size())
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+
+    pub fn left(&self) -> f32 {
+        -self.width / 2.0
+    }
+
+    pub fn right(&self) -> f32 {
+        self.width / 2.0
+    }
+
+    pub fn saucer_height(&self) -> f32 {
+        self.formation_top + self.saucer_above
+    }
+
+    pub fn bunkers_height(&self) -> f32 {
+        self.bat_height + self.bunkers_above
+    }
+
+    pub fn lose_line(&self) -> f32 {
+        self.bat_height + self.lose_above
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatConfig {
+    pub mass: f32,
+    pub drag: f32,
+    pub force: f32,
+    pub shoot_delay: f32,
+    pub bullet_speed: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct AliensConfig {
+    pub rows: u32,
+    pub columns: u32,
+    pub march_delay: f32,
+    pub march_curve: MarchCurveConfig,
+    pub bullet_speed: f32,
+    /// Chance that an alien shoots when the fire delay is over.
+    pub fire_chance: f32,
+    /// Only aliens closer than this to the bat shoot.
+    pub aim_range: f32,
+    pub fire_delay: FireDelayConfig,
+    /// Alien kinds, the formations of `waves` refer to them by name.
+    pub kinds: Vec<AlienKindConfig>,
+    pub pixel_art: PixelArtConfig,
+}
+
+/// Generated looks of `Pixels` aliens, a different one for every row of every wave.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct PixelArtConfig {
+    pub seed: u64,
+    /// Width and height in pixels.
+    pub size: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlienKindConfig {
+    pub name: String,
+    /// Score for killing one.
+    pub points: i32,
+    pub color: (u8, u8, u8),
+    pub shape: AlienShape,
+    /// Hits needed to kill one.
+    pub hp: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AlienShape {
+    /// Generated pixel art, see `PixelArtConfig`.
+    Pixels,
+    Square,
+    Circle,
+    Diamond,
+}
+
+/// How the march delay shrinks as aliens die:
+/// `min_factor + (1 - min_factor) * (alive / spawned) ^ exponent`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarchCurveConfig {
+    pub min_factor: f32,
+    pub exponent: f32,
+}
+
+/// Delay between alien shots: `base / (offset + time / ramp)`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct FireDelayConfig {
+    pub base: f32,
+    pub offset: f32,
+    pub ramp: f32,
+}
+
+/// How each wave after the first gets harder.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct WavesConfig {
+    /// Waves to clear for a win, 0 plays until the game is lost.
+    pub count: u32,
+    /// How much lower each wave starts, up to `max_drop`, which must keep the formation above the lose line.
+    pub start_drop: f32,
+    pub max_drop: f32,
+    /// March and fire delays are multiplied by these once per wave.
+    pub march_speedup: f32,
+    pub fire_speedup: f32,
+    /// Alien kind of each row from the top, one list per wave.
+    /// The last row repeats for the remaining rows, the last formation for the remaining waves.
+    pub formations: Vec<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct LivesConfig {
+    pub start: u32,
+    /// Seconds the bat is gone after a hit.
+    pub respawn_delay: f32,
+    /// Seconds the bat can't be hit after respawning.
+    pub invulnerable: f32,
+    /// Each of these scores gives one extra life, in increasing order.
+    pub extra_life_scores: Vec<i32>,
+}
+
+/// The mystery ship crossing the top of the arena.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct SaucerConfig {
+    /// Seconds between saucers, picked at random in this range.
+    pub min_delay: f32,
+    pub max_delay: f32,
+    pub speed: f32,
+    /// Bonus for a hit, one of these picked at random.
+    pub points: Vec<i32>,
+    /// No saucer comes when fewer aliens are left.
+    pub min_aliens: u32,
+}
+
+/// Shields spread evenly across the arena.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct BunkersConfig {
+    pub count: u32,
+    pub cell_size: f32,
+    /// Rows of cells from the top, `#` is a cell, anything else a gap.
+    pub shape: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreConfig {
+    pub shot: i32,
+    pub per_second: i32,
+}
+
+impl Default for InvadersConfig {
+    fn default() -> Self {
+        Self {
+            arena: default(),
+            bat: default(),
+            aliens: default(),
+            score: default(),
+            waves: default(),
+            lives: default(),
+            saucer: default(),
+            bunkers: default(),
+        }
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self {
+            width: 1280.0,
+            height: 840.0,
+            bat_height: -360.0,
+            formation_top: 400.0,
+            formation_bottom: -100.0,
+            march_room: 180.0,
+            saucer_above: 10.0,
+            bunkers_above: 110.0,
+            lose_above: 10.0,
+        }
+    }
+}
+
+impl Default for BatConfig {
+    fn default() -> Self {
+        Self {
+            mass: 1.0,
+            drag: 1.0,
+            force: 100.0,
+            shoot_delay: 1.5,
+            bullet_speed: 100.0,
+        }
+    }
+}
+
+impl Default for AliensConfig {
+    fn default() -> Self {
+        Self {
+            rows: 6,
+            columns: 11,
+            march_delay: 1.0,
+            march_curve: default(),
+            bullet_speed: 150.0,
+            fire_chance: 0.5,
+            aim_range: 200.0,
+            fire_delay: default(),
+            pixel_art: default(),
+            kinds: vec![
+                AlienKindConfig {
+                    name: "squid".into(),
+                    points: 30,
+                    color: (211, 118, 118),
+                    shape: AlienShape::Pixels,
+                    hp: 1,
+                },
+                AlienKindConfig {
+                    name: "crab".into(),
+                    points: 20,
+                    color: (81, 130, 155),
+                    shape: AlienShape::Pixels,
+                    hp: 1,
+                },
+                AlienKindConfig {
+                    name: "octopus".into(),
+                    points: 10,
+                    color: (155, 176, 193),
+                    shape: AlienShape::Pixels,
+                    hp: 1,
+                },
+            ],
+        }
+    }
+}
+
+impl Default for AlienKindConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            points: 10,
+            color: (81, 130, 155),
+            shape: AlienShape::Square,
+            hp: 1,
+        }
+    }
+}
+
+impl Default for PixelArtConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1978,
+            size: 8,
+        }
+    }
+}
+
+impl Default for MarchCurveConfig {
+    fn default() -> Self {
+        Self {
+            min_factor: 0.05,
+            exponent: 1.0,
+        }
+    }
+}
+
+impl Default for FireDelayConfig {
+    fn default() -> Self {
+        Self {
+            base: 9.0,
+            offset: 3.0,
+            ramp: 10.0,
+        }
+    }
+}
+
+impl Default for WavesConfig {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            start_drop: 30.0,
+            max_drop: 150.0,
+            march_speedup: 0.85,
+            fire_speedup: 0.85,
+            formations: vec![
+                ["squid", "crab", "crab", "octopus"].map(String::from).to_vec(),
+            ],
+        }
+    }
+}
+
+impl Default for LivesConfig {
+    fn default() -> Self {
+        Self {
+            start: 3,
+            respawn_delay: 1.5,
+            invulnerable: 2.0,
+            extra_life_scores: vec![1500, 5000],
+        }
+    }
+}
+
+impl Default for SaucerConfig {
+    fn default() -> Self {
+        Self {
+            min_delay: 15.0,
+            max_delay: 30.0,
+            speed: 120.0,
+            points: vec![50, 100, 150, 300],
+            min_aliens: 8,
+        }
+    }
+}
+
+impl Default for BunkersConfig {
+    fn default() -> Self {
+        Self {
+            count: 4,
+            cell_size: 8.0,
+            shape: [
+                "   ######   ",
+                "  ########  ",
+                " ########## ",
+                "############",
+                "############",
+                "####    ####",
+                "###      ###",
+            ].map(String::from).to_vec(),
+        }
+    }
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            shot: -10,
+            per_second: -2,
+        }
+    }
+}
+
+impl FireDelayConfig {
+    pub fn delay(&self, time: f32) -> f32 {
+        self.base / (self.offset + time / self.ramp)
+    }
+}
+
+impl MarchCurveConfig {
+    pub fn factor(&self, alive: usize, spawned: usize) -> f32 {
+        let left = alive as f32 / spawned.max(1) as f32;
+        self.min_factor + (1.0 - self.min_factor) * left.powf(self.exponent)
+    }
+}
+
+impl WavesConfig {
+    pub fn drop(&self, wave: u32) -> f32 {
+        (self.start_drop * (wave - 1) as f32).min(self.max_drop)
+    }
+
+    pub fn march_factor(&self, wave: u32) -> f32 {
+        self.march_speedup.powi(wave as i32 - 1)
+    }
+
+    pub fn fire_factor(&self, wave: u32) -> f32 {
+        self.fire_speedup.powi(wave as i32 - 1)
+    }
+
+    /// Kind name of a row in a wave, both counted from 1.
+    pub fn row_kind(&self, wave: u32, row: u32) -> &str {
+        let formation = &self.formations[(wave as usize - 1).min(self.formations.len() - 1)];
+        &formation[(row as usize - 1).min(formation.len() - 1)]
+    }
+}
+
+impl AliensConfig {
+    pub fn kind_index(&self, name: &str) -> Option<usize> {
+        self.kinds.iter().position(|k| k.name == name)
+    }
+}
+
+impl InvadersConfig {
+    /// Reads and validates a config file, a missing file gives the defaults.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                info!("{} not found, using default config", path.display());
+                Ok(default())
+            }
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = ron::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks value ranges, reporting all problems at once.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = String::new();
+        let mut check = |ok: bool, field: &str, rule: &str, value: &dyn std::fmt::Debug| {
+            if !ok {
+                let _ = write!(errors, "\n  {} must be {}, got {:?}", field, rule, value);
+            }
+        };
+
+        let arena = &self.arena;
+        let inside = (-arena.height / 2.0)..=(arena.height / 2.0);
+        let inside_rule = format!("in {:?}", inside);
+        check(arena.width > 0.0, "arena.width", "> 0", &arena.width);
+        check(arena.height > 0.0, "arena.height", "> 0", &arena.height);
+        check(inside.contains(&arena.bat_height), "arena.bat_height", &inside_rule, &arena.bat_height);
+        check(inside.contains(&arena.formation_top), "arena.formation_top", &inside_rule, &arena.formation_top);
+        check(arena.formation_bottom < arena.formation_top, "arena.formation_bottom", "< arena.formation_top", &arena.formation_bottom);
+        check((0.0..arena.width).contains(&arena.march_room), "arena.march_room", "in 0..arena.width", &arena.march_room);
+        check(arena.saucer_above >= 0.0 && inside.contains(&arena.saucer_height()), "arena.saucer_above", "in 0..=arena.height / 2 - arena.formation_top", &arena.saucer_above);
+        check(arena.bunkers_above > 0.0 && arena.bunkers_height() < arena.formation_bottom, "arena.bunkers_above", "in (0, arena.formation_bottom - arena.bat_height)", &arena.bunkers_above);
+        check(arena.lose_above >= 0.0 && arena.lose_line() < arena.formation_bottom, "arena.lose_above", "in [0, arena.formation_bottom - arena.bat_height)", &arena.lose_above);
+
+        let bat = &self.bat;
+        check(bat.mass > 0.0, "bat.mass", "> 0", &bat.mass);
+        check(bat.drag >= 0.0, "bat.drag", ">= 0", &bat.drag);
+        check(bat.force > 0.0, "bat.force", "> 0", &bat.force);
+        check(bat.shoot_delay >= 0.0, "bat.shoot_delay", ">= 0", &bat.shoot_delay);
+        check(bat.bullet_speed > 0.0, "bat.bullet_speed", "> 0", &bat.bullet_speed);
+
+        let aliens = &self.aliens;
+        check((1..=20).contains(&aliens.rows), "aliens.rows", "in 1..=20", &aliens.rows);
+        check((1..=30).contains(&aliens.columns), "aliens.columns", "in 1..=30", &aliens.columns);
+        check(aliens.march_delay > 0.0, "aliens.march_delay", "> 0", &aliens.march_delay);
+        check(aliens.march_curve.min_factor > 0.0 && aliens.march_curve.min_factor <= 1.0, "aliens.march_curve.min_factor", "in (0, 1]", &aliens.march_curve.min_factor);
+        check(aliens.march_curve.exponent > 0.0, "aliens.march_curve.exponent", "> 0", &aliens.march_curve.exponent);
+        check(aliens.bullet_speed > 0.0, "aliens.bullet_speed", "> 0", &aliens.bullet_speed);
+        check((0.0..=1.0).contains(&aliens.fire_chance), "aliens.fire_chance", "in 0..=1", &aliens.fire_chance);
+        check(aliens.aim_range > 0.0, "aliens.aim_range", "> 0", &aliens.aim_range);
+        check(aliens.fire_delay.base > 0.0, "aliens.fire_delay.base", "> 0", &aliens.fire_delay.base);
+        check(aliens.fire_delay.offset > 0.0, "aliens.fire_delay.offset", "> 0", &aliens.fire_delay.offset);
+        check(aliens.fire_delay.ramp > 0.0, "aliens.fire_delay.ramp", "> 0", &aliens.fire_delay.ramp);
+        check((4..=16).contains(&aliens.pixel_art.size), "aliens.pixel_art.size", "in 4..=16", &aliens.pixel_art.size);
+        check(!aliens.kinds.is_empty(), "aliens.kinds", "not empty", &aliens.kinds.len());
+        for (i, kind) in aliens.kinds.iter().enumerate() {
+            check(aliens.kind_index(&kind.name) == Some(i), "aliens.kinds.name", "unique", &kind.name);
+            check(kind.hp >= 1, "aliens.kinds.hp", ">= 1", &kind.hp);
+        }
+
+        let waves = &self.waves;
+        check(waves.start_drop >= 0.0, "waves.start_drop", ">= 0", &waves.start_drop);
+        // a wave starting on the lose line would be lost on its first tick
+        let max_drop = arena.formation_bottom - arena.lose_line();
+        check((0.0..max_drop).contains(&waves.max_drop), "waves.max_drop", &format!("in 0..{} (arena.formation_bottom - lose line)", max_drop), &waves.max_drop);
+        check(waves.march_speedup > 0.0 && waves.march_speedup <= 1.0, "waves.march_speedup", "in (0, 1]", &waves.march_speedup);
+        check(waves.fire_speedup > 0.0 && waves.fire_speedup <= 1.0, "waves.fire_speedup", "in (0, 1]", &waves.fire_speedup);
+        check(!waves.formations.is_empty(), "waves.formations", "not empty", &waves.formations.len());
+        for formation in &waves.formations {
+            check(!formation.is_empty(), "waves.formations", "lists of kinds", formation);
+            for name in formation {
+                check(aliens.kind_index(name).is_some(), "waves.formations", "names from aliens.kinds", name);
+            }
+        }
+
+        let lives = &self.lives;
+        check((1..=9).contains(&lives.start), "lives.start", "in 1..=9", &lives.start);
+        check(lives.respawn_delay >= 0.0, "lives.respawn_delay", ">= 0", &lives.respawn_delay);
+        check(lives.invulnerable >= 0.0, "lives.invulnerable", ">= 0", &lives.invulnerable);
+        check(lives.extra_life_scores.windows(2).all(|w| w[0] < w[1]), "lives.extra_life_scores", "increasing", &lives.extra_life_scores);
+
+        let saucer = &self.saucer;
+        check(saucer.min_delay > 0.0, "saucer.min_delay", "> 0", &saucer.min_delay);
+        check(saucer.max_delay >= saucer.min_delay, "saucer.max_delay", ">= saucer.min_delay", &saucer.max_delay);
+        check(saucer.speed > 0.0, "saucer.speed", "> 0", &saucer.speed);
+        check(!saucer.points.is_empty(), "saucer.points", "not empty", &saucer.points);
+
+        let bunkers = &self.bunkers;
+        check(bunkers.count <= 10, "bunkers.count", "<= 10", &bunkers.count);
+        check(bunkers.cell_size > 0.0, "bunkers.cell_size", "> 0", &bunkers.cell_size);
+        check(bunkers.count == 0 || bunkers.shape.iter().any(|row| row.contains('#')), "bunkers.shape", "rows with at least one #", &bunkers.shape);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("invalid config:{}", errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(InvadersConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn shipped_config_is_valid() {
+        InvadersConfig::parse(include_str!("../../assets/invaders.ron")).unwrap();
+    }
+
+    #[test]
+    fn every_bad_field_is_reported() {
+        let mut config = InvadersConfig::default();
+        config.bat.mass = 0.0;
+        config.aliens.rows = 0;
+        config.aliens.fire_chance = 2.0;
+        config.waves.formations = vec![vec!["nobody".into()]];
+        config.lives.start = 10;
+        config.saucer.max_delay = config.saucer.min_delay - 1.0;
+        let errors = config.validate().unwrap_err();
+        for field in ["bat.mass", "aliens.rows", "aliens.fire_chance", "waves.formations", "lives.start", "saucer.max_delay"] {
+            assert!(errors.contains(&format!("\n  {} must be", field)), "{} not reported in: {}", field, errors);
+        }
+        assert_eq!(errors.lines().count(), 7, "one line per problem after the heading: {}", errors);
+    }
+
+    #[test]
+    fn max_drop_stops_above_the_lose_line() {
+        let mut config = InvadersConfig::default();
+        let to_lose_line = config.arena.formation_bottom - config.arena.lose_line();
+        config.waves.max_drop = to_lose_line - 1.0;
+        assert_eq!(config.validate(), Ok(()));
+        config.waves.max_drop = to_lose_line;
+        assert!(config.validate().unwrap_err().contains("waves.max_drop"));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(InvadersConfig::parse("(bat: (mas: 2.0))").is_err());
+    }
+}
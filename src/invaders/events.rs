@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+/// Facts of a running game, sent by the systems that find them out.
+///
+/// Score, lives, sound and whatever else wants to know subscribe to these
+/// instead of being wired into the collision systems.
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<AlienKilled>()
+            .add_event::<PlayerHit>()
+            .add_event::<ShotFired>()
+            .add_event::<WaveCleared>()
+            .add_event::<FormationStepped>()
+            .add_systems(Update, log_events)
+        ;
+    }
+}
+
+/// An alien, or the saucer, lost its last hit point.
+#[derive(Event, Debug)]
+pub struct AlienKilled {
+    pub pos: Vec2,
+    pub points: i32,
+    pub saucer: bool,
+}
+
+/// An alien bullet hit the bat.
+#[derive(Event, Debug)]
+pub struct PlayerHit {
+    pub pos: Vec2,
+}
+
+/// A bullet was spawned.
+#[derive(Event, Debug)]
+pub struct ShotFired {
+    pub pos: Vec2,
+    pub alien: bool,
+}
+
+/// The last alien of a wave died.
+#[derive(Event, Debug)]
+pub struct WaveCleared {
+    pub wave: u32,
+}
+
+/// The formation marched one step.
+#[derive(Event, Debug)]
+pub struct FormationStepped {
+    pub steps: u32,
+}
+
+fn log_events(
+    mut killed: EventReader<AlienKilled>,
+    mut hit: EventReader<PlayerHit>,
+    mut shots: EventReader<ShotFired>,
+    mut cleared: EventReader<WaveCleared>,
+) {
+    for kill in killed.read() {
+        debug!("{} killed at {} for {} points", if kill.saucer { "saucer" } else { "alien" }, kill.pos, kill.points);
+    }
+    for hit in hit.read() {
+        debug!("player hit at {}", hit.pos);
+    }
+    for shot in shots.read() {
+        debug!("{} shot from {}", if shot.alien { "alien" } else { "player" }, shot.pos);
+    }
+    for wave in cleared.read() {
+        debug!("wave {} cleared", wave.wave);
+    }
+}
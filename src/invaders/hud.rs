@@ -14,6 +14,7 @@ impl Plugin for HudPlugin {
             .add_systems(OnEnter(MainState::Invaders), create_hud)
             .add_systems(OnExit(MainState::Invaders), destroy_hud)
             .add_systems(Update, update_hud.run_if(in_state(InvaderState::Game)))
+            .add_systems(Update, update_lives.run_if(in_state(InvaderState::Game)))
         ;
     }
 }
@@ -21,10 +22,28 @@ impl Plugin for HudPlugin {
 #[derive(Component)]
 struct MHud;
 
+#[derive(Component)]
+struct MHudLives;
+
 fn create_hud(
     mut commands: Commands,
     assets: Res<AssetServer>
 ) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Percent(5.0),
+                bottom: Val::Px(20.0),
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        },
+        MHud,
+        MHudLives,
+    ));
+
     let Some(font) = assets.get_handle("eight-bit-dragon.otf") else {
         error!("menu font not loaded");
         return;
@@ -41,7 +60,7 @@ fn create_hud(
             style: Style {
                 position_type: PositionType::Absolute,
                 left: Val::Percent(5.0),
-                top: Val::Px(795.0),
+                bottom: Val::Px(10.0),
                 ..default()
             },
             ..default()
@@ -59,11 +78,37 @@ fn destroy_hud(
     }
 }
 
+fn update_lives(
+    mut commands: Commands,
+    qlives: Query<(Entity, Option<&Children>), With<MHudLives>>,
+    game: Res<InvadersGame>,
+) {
+    let Ok((e, children)) = qlives.get_single() else {
+        return;
+    };
+    if children.map_or(0, |c| c.len()) == game.lives as usize {
+        return;
+    }
+    commands.entity(e).despawn_descendants().with_children(|commands| {
+        for _ in 0..game.lives {
+            commands.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(40.0),
+                    height: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb_u8(246, 153, 92)),
+                ..default()
+            });
+        }
+    });
+}
+
 fn update_hud(
     mut qscore: Query<&mut Text, With<MHud>>,
     game: Res<InvadersGame>,
 ) {
     if let Ok(mut text) = qscore.get_single_mut() {
-        text.sections[0].value = format!("Score: {}", game.score);
+        text.sections[0].value = format!("Wave: {}  Score: {}", game.wave, game.score);
     }
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::actions::{Action, BatControl, ControlSettings, RawInputs};
+use crate::fps_counter::CursorWorldPos;
+use crate::invaders::bat::MBat;
+use crate::invaders::config::InvadersConfig;
+
+/// What the player wants the bat to do in the current tick.
+///
+/// Gameplay reads this instead of the raw buttons, so a replay can provide it.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlayerInput {
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+    /// Cheat, held while `Action::SkipWave` is.
+    pub skip_wave: bool,
+    /// Analog steering from a gamepad stick or the mouse, -127 to 127, used over `left` and `right` unless 0.
+    pub steer: i8,
+}
+
+impl PlayerInput {
+    pub fn to_bits(self) -> u16 {
+        (self.left as u16) | ((self.right as u16) << 1) | ((self.fire as u16) << 2) | ((self.skip_wave as u16) << 3) | ((self.steer as u8 as u16) << 8)
+    }
+
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            left: bits & 1 != 0,
+            right: bits & 2 != 0,
+            fire: bits & 4 != 0,
+            skip_wave: bits & 8 != 0,
+            steer: (bits >> 8) as u8 as i8,
+        }
+    }
+
+    /// Where the bat is pushed, from -1 (left) to 1 (right).
+    pub fn steering(self) -> f32 {
+        if self.steer != 0 {
+            self.steer as f32 / 127.0
+        } else {
+            self.right as i32 as f32 - self.left as i32 as f32
+        }
+    }
+}
+
+pub fn read_input(
+    actions: Res<ButtonInput<Action>>,
+    inputs: RawInputs,
+    settings: Res<ControlSettings>,
+    cursor: Option<Res<CursorWorldPos>>,
+    qbat: Query<(&MBat, &Transform)>,
+    config: Res<InvadersConfig>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let mut steering = inputs.pad_axis(GamepadAxisType::LeftStickX);
+    // the stick wins over the mouse, like over the move actions
+    if settings.bat == BatControl::Mouse && steering == 0.0 {
+        if let (Some(CursorWorldPos(Some(target))), Ok((bat, t))) = (cursor.as_deref(), qbat.get_single()) {
+            steering = bat.chase(t.translation.x, target.x, &config.bat);
+        }
+    }
+    *input = PlayerInput {
+        left: actions.pressed(Action::MoveLeft),
+        right: actions.pressed(Action::MoveRight),
+        fire: actions.pressed(Action::Fire),
+        skip_wave: actions.pressed(Action::SkipWave),
+        steer: (steering * 127.0).round() as i8,
+    };
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::invaders::save::ReflectSave;
+
+/// Smooths entities moved in `FixedUpdate` between two fixed ticks.
+///
+/// The simulation always sees the position of the last tick in `Transform`,
+/// rendering sees a position between the last two ticks.
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_type::<Interpolated>()
+            .add_systems(FixedFirst, restore_translation)
+            .add_systems(FixedLast, record_translation)
+            .add_systems(PostUpdate, interpolate_translation.before(TransformSystem::TransformPropagate))
+        ;
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component, Save)]
+pub struct Interpolated {
+    prev: Vec3,
+    next: Vec3,
+}
+
+impl Interpolated {
+    pub fn at(pos: Vec3) -> Self {
+        Self { prev: pos, next: pos }
+    }
+
+    /// Jump to a position without smoothing, use when teleporting in `FixedUpdate`.
+    pub fn reset(&mut self, pos: Vec3) {
+        self.prev = pos;
+        self.next = pos;
+    }
+}
+
+fn restore_translation(mut q: Query<(&Interpolated, &mut Transform)>) {
+    for (i, mut t) in q.iter_mut() {
+        t.translation = i.next;
+    }
+}
+
+fn record_translation(mut q: Query<(&mut Interpolated, &Transform)>) {
+    for (mut i, t) in q.iter_mut() {
+        i.prev = i.next;
+        i.next = t.translation;
+    }
+}
+
+fn interpolate_translation(
+    time: Res<Time<Fixed>>,
+    mut q: Query<(&Interpolated, &mut Transform)>,
+) {
+    let s = time.overstep_fraction();
+    for (i, mut t) in q.iter_mut() {
+        t.translation = i.prev.lerp(i.next, s);
+    }
+}
@@ -1,26 +1,44 @@
 use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use crate::actions::Action;
+use crate::focus::FocusDefault;
+use crate::highscores::{self, HighScore, HighScores};
+use crate::main_menu::MMenuStyles;
 use crate::invaders::{InvadersGame, InvaderState};
+use crate::invaders::save::SaveEvent;
+use crate::sound::SoundSettings;
 use crate::MainState;
 
 #[derive(Component)]
-pub struct MenuPlugin;
+pub struct MenuPlugin {
+    pub presentation: bool,
+}
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<EventClose>()
 
-            .add_systems(OnEnter(InvaderState::Start), show_menu)
-            .add_systems(OnEnter(InvaderState::Pause), show_menu)
-            .add_systems(OnEnter(InvaderState::Win), show_menu)
-            .add_systems(OnEnter(InvaderState::Gameover), show_menu)
-            .add_systems(OnEnter(InvaderState::Game), destroy_menu)
-            .add_systems(OnEnter(InvaderState::None), destroy_menu)
             .add_systems(Update, interact_key.run_if(in_menu))
-            .add_systems(Update, interact_menu.run_if(in_menu))
-            .add_systems(Update, hover_menu.run_if(in_menu))
             .add_systems(Update, close_menu.run_if(in_menu))
         ;
+
+        if self.presentation {
+            app
+                .add_systems(OnEnter(InvaderState::Start), show_menu)
+                .add_systems(OnEnter(InvaderState::Pause), show_menu)
+                .add_systems(OnEnter(InvaderState::Win), (record_score, show_menu, forget_typed).chain())
+                .add_systems(OnEnter(InvaderState::Gameover), (record_score, show_menu, forget_typed).chain())
+                .add_systems(OnExit(InvaderState::Win), save_scores)
+                .add_systems(OnExit(InvaderState::Gameover), save_scores)
+                .add_systems(OnEnter(InvaderState::Game), destroy_menu)
+                .add_systems(OnEnter(InvaderState::None), destroy_menu)
+                .add_systems(Update, interact_menu.run_if(in_menu))
+                .add_systems(Update, hover_menu.run_if(in_menu))
+                .add_systems(Update, interact_volume.run_if(in_state(InvaderState::Pause)))
+                .add_systems(Update, edit_name.run_if(in_state(InvaderState::Win).or_else(in_state(InvaderState::Gameover))))
+            ;
+        }
     }
 }
 
@@ -33,6 +51,38 @@ struct MMenuQuit;
 #[derive(Component)]
 struct MMenuClose;
 
+#[derive(Component)]
+struct MMenuName;
+
+#[derive(Component)]
+struct MMenuSave;
+
+#[derive(Component, Clone, Copy)]
+enum MMenuVolume {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl MMenuVolume {
+    fn value(self, settings: &mut SoundSettings) -> &mut f32 {
+        match self {
+            MMenuVolume::Master => &mut settings.master,
+            MMenuVolume::Music => &mut settings.music,
+            MMenuVolume::Sfx => &mut settings.sfx,
+        }
+    }
+
+    fn label(self, settings: &mut SoundSettings) -> String {
+        let name = match self {
+            MMenuVolume::Master => "Volume",
+            MMenuVolume::Music => "Music",
+            MMenuVolume::Sfx => "Effects",
+        };
+        format!("{}: {:.0}%", name, *self.value(settings) * 100.0)
+    }
+}
+
 #[derive(Event)]
 struct EventClose;
 
@@ -51,12 +101,15 @@ fn show_menu(
     game: Res<InvadersGame>,
     assets: Res<AssetServer>,
     state: Res<State<InvaderState>>,
+    scores: Option<Res<HighScores>>,
+    sound: Option<ResMut<SoundSettings>>,
 ) {
     let Some(font) = assets.get_handle("eight-bit-dragon.otf") else {
         error!("menu font not loaded");
         return;
     };
     let color = Color::rgb_u8(81, 130, 155);
+    let can_close = *state.get() != InvaderState::Win && *state.get() != InvaderState::Gameover;
     commands.spawn((
         NodeBundle {
             style: Style {
@@ -71,7 +124,7 @@ fn show_menu(
         },
         MMenu,
         ));
-    commands.spawn((
+    let quit = commands.spawn((
         TextBundle {
             text: Text::from_section("Quit", TextStyle {
                 font: font.clone(),
@@ -89,8 +142,8 @@ fn show_menu(
         Interaction::default(),
         MMenu,
         MMenuQuit,
-    ));
-    if *state.get() != InvaderState::Win && *state.get() != InvaderState::Gameover {
+    )).id();
+    if can_close {
         commands.spawn((
             TextBundle {
                 text: Text::from_section(match state.get() {InvaderState::Start => "Start", _ => "Close"}, TextStyle {
@@ -109,7 +162,54 @@ fn show_menu(
             Interaction::default(),
             MMenu,
             MMenuClose,
+            FocusDefault,
+        ));
+    } else {
+        commands.entity(quit).insert(FocusDefault);
+    }
+    if *state.get() == InvaderState::Pause {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section("Save", TextStyle {
+                    font: font.clone(),
+                    font_size: 30.0,
+                    color,
+                }),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(45.0),
+                    top: Val::Percent(90.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Interaction::default(),
+            MMenu,
+            MMenuSave,
         ));
+        if let Some(mut sound) = sound {
+            for (i, volume) in [MMenuVolume::Master, MMenuVolume::Music, MMenuVolume::Sfx].into_iter().enumerate() {
+                commands.spawn((
+                    TextBundle {
+                        text: Text::from_section(volume.label(sound.bypass_change_detection()), TextStyle {
+                            font: font.clone(),
+                            font_size: 30.0,
+                            color,
+                        }),
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Percent(40.0),
+                            top: Val::Percent(55.0 + 5.0 * i as f32),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Interaction::default(),
+                    MMenu,
+                    volume,
+                ));
+            }
+        }
     }
     match state.get() {
         InvaderState::Win => {
@@ -185,11 +285,118 @@ fn show_menu(
             },
             MMenu,
         ));
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section(format!("Wave: {}", game.wave), TextStyle {
+                    font: font.clone(),
+                    font_size: 30.0,
+                    color,
+                }),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(40.0),
+                    top: Val::Percent(40.0),
+                    ..default()
+                },
+                ..default()
+            },
+            MMenu,
+        ));
+    }
+    let new_entry = scores.as_ref().and_then(|s| s.last.map(|pos| (pos, s.entries[pos].name.clone())));
+    if let Some((pos, name)) = new_entry {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_sections([
+                    TextSection::new(format!("High score #{}!\nName: ", pos + 1), TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color,
+                    }),
+                    TextSection::new(name, TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::rgb_u8(211, 118, 118),
+                    }),
+                ]),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(40.0),
+                    top: Val::Percent(50.0),
+                    ..default()
+                },
+                ..default()
+            },
+            MMenu,
+            MMenuName,
+        ));
     }
 }
 
-fn interact_key(
+fn record_score(
+    game: Res<InvadersGame>,
+    scores: Option<ResMut<HighScores>>,
+) {
+    let Some(mut scores) = scores else {
+        return;
+    };
+    let name = if scores.name.is_empty() { "Player".into() } else { scores.name.clone() };
+    let entry = HighScore {
+        name,
+        score: game.score,
+        time: game.time,
+        wave: game.wave,
+        date: highscores::today(),
+    };
+    if scores.insert(entry).is_some() {
+        scores.save();
+    }
+}
+
+/// Keys pressed while playing are still queued as characters, they don't belong in the name.
+fn forget_typed(mut chars: ResMut<Events<ReceivedCharacter>>) {
+    chars.clear();
+}
+
+fn edit_name(
+    mut chars: EventReader<ReceivedCharacter>,
     input: Res<ButtonInput<KeyCode>>,
+    scores: Option<ResMut<HighScores>>,
+    mut qname: Query<&mut Text, With<MMenuName>>,
+) {
+    let Some(mut scores) = scores else {
+        return;
+    };
+    let Some(last) = scores.last else {
+        return;
+    };
+    let mut name = scores.entries[last].name.clone();
+    if input.just_pressed(KeyCode::Backspace) {
+        name.pop();
+    }
+    for c in chars.read().flat_map(|e| e.char.chars()) {
+        if !c.is_control() && name.chars().count() < HighScores::NAME_LEN {
+            name.push(c);
+        }
+    }
+    if name == scores.entries[last].name {
+        return;
+    }
+    scores.entries[last].name = name.clone();
+    scores.name = name.clone();
+    if let Ok(mut text) = qname.get_single_mut() {
+        text.sections[1].value = name;
+    }
+}
+
+fn save_scores(scores: Option<Res<HighScores>>) {
+    if let Some(scores) = scores {
+        scores.save();
+    }
+}
+
+fn interact_key(
+    actions: Res<ButtonInput<Action>>,
     mut event_close: EventWriter<EventClose>,
     // game: Res<InvadersGame>,
     menu_state: Res<State<InvaderState>>,
@@ -198,29 +405,55 @@ fn interact_key(
         InvaderState::Win => return,
         InvaderState::Gameover => return,
         _=> {
-            if input.just_pressed(KeyCode::Escape) {
+            if actions.just_pressed(Action::Pause) {
                 event_close.send(EventClose);
             }
         }
     }
 }
 
+/// Menu buttons of type `T` whose interaction changed this frame.
+type Interacted<T> = (With<T>, Changed<Interaction>);
+
 fn interact_menu(
-    qclose: Query<&Interaction, (With<MMenuClose>, Changed<Interaction>)>,
-    qquit: Query<&Interaction, (With<MMenuQuit>, Changed<Interaction>)>,
+    qclose: Query<&Interaction, Interacted<MMenuClose>>,
+    qquit: Query<&Interaction, Interacted<MMenuQuit>>,
+    mut qsave: Query<(&Interaction, &mut Text), Interacted<MMenuSave>>,
     mut event_close: EventWriter<EventClose>,
+    mut event_save: EventWriter<SaveEvent>,
     mut main_state: ResMut<NextState<MainState>>,
     mut state: ResMut<NextState<InvaderState>>,
 ) {
     if let Ok(Interaction::Pressed) = qclose.get_single() {
         event_close.send(EventClose);
     }
+    if let Ok((Interaction::Pressed, mut text)) = qsave.get_single_mut() {
+        event_save.send(SaveEvent);
+        text.sections[0].value = "Saved".into();
+    }
     if let Ok(Interaction::Pressed) = qquit.get_single() {
         state.set(InvaderState::None);
         main_state.set(MainState::MainMenu);
     }
 }
 
+/// Each click turns a volume up by 20%, past 100% it starts over from 0.
+fn interact_volume(
+    mut q: Query<(&Interaction, &MMenuVolume, &mut Text), Changed<Interaction>>,
+    sound: Option<ResMut<SoundSettings>>,
+) {
+    let Some(mut sound) = sound else {
+        return;
+    };
+    for (int, volume, mut text) in q.iter_mut() {
+        if *int == Interaction::Pressed {
+            let value = volume.value(&mut sound);
+            *value = if *value >= 0.99 { 0.0 } else { (*value + 0.2).min(1.0) };
+            text.sections[0].value = volume.label(&mut sound);
+        }
+    }
+}
+
 fn close_menu(
     event: EventReader<EventClose>,
     mut state: ResMut<NextState<InvaderState>>,
@@ -240,11 +473,12 @@ fn destroy_menu(
 }
 
 fn hover_menu(
-    mut qmenu: Query<(&Interaction, &mut BackgroundColor), (With<MMenu>, Changed<Interaction>)>
+    mut qmenu: Query<(&Interaction, &mut BackgroundColor), (With<MMenu>, Changed<Interaction>)>,
+    styles: Res<MMenuStyles>,
 ) {
     for (int, mut color) in qmenu.iter_mut() {
         match int {
-            Interaction::Hovered => { *color = BackgroundColor(Color::rgba_u8(155, 176, 193, 96)); }
+            Interaction::Hovered => { *color = BackgroundColor(styles.hover_color); }
             Interaction::Pressed => {}
             Interaction::None => { *color = BackgroundColor(Color::NONE); }
         }
@@ -1,79 +1,316 @@
 use bevy::app::{App, Plugin};
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
+use bevy::hierarchy::HierarchyPlugin;
+use bevy::input::InputPlugin;
+use bevy::transform::TransformPlugin;
+use bevy::window::PrimaryWindow;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
 
 use crate::invaders::aliens::AliensPlugin;
 use crate::invaders::bat::BatPlugin;
 use crate::invaders::bullet::BulletPlugin;
+use crate::invaders::bunker::BunkerPlugin;
+use crate::invaders::collision::CollisionPlugin;
+use crate::invaders::config::{Arena, InvadersConfig};
+use crate::invaders::events::{AlienKilled, EventsPlugin, ShotFired};
 use crate::invaders::hud::HudPlugin;
+use crate::invaders::input::PlayerInput;
+use crate::invaders::interpolation::InterpolationPlugin;
 use crate::invaders::menu::{MenuPlugin};
+use crate::invaders::replay::{Playback, Recorder, Replay};
+use crate::invaders::saucer::SaucerPlugin;
+use crate::invaders::save::{ReflectSave, SaveEvent};
+use crate::invaders::touch::TouchPlugin;
+use crate::actions::{Action, ActionsPlugin, ActionsSystem};
+use crate::fps_counter::DebugInfo;
+use crate::sound::{PlaySound, Sfx, SoundPaused};
 use crate::MainState;
 
 mod bat;
 mod aliens;
 mod bullet;
+mod bunker;
+mod collision;
+pub mod events;
 mod menu;
 mod hud;
+pub mod config;
+mod interpolation;
+mod input;
+mod replay;
+mod save;
+mod saucer;
+mod sprite;
+mod pixel_art;
+mod touch;
+
+pub use save::{has_saved_game, load_from_text, save_to_text, ResumeGame};
 
 pub struct InvadersPlugin<T: States+Copy> {
     mystate: T,
+    presentation: bool,
+    seed: Option<u64>,
+    config: Option<std::path::PathBuf>,
+    replay: Option<std::path::PathBuf>,
+    record: Option<std::path::PathBuf>,
 }
 
 impl<T: States+Copy> InvadersPlugin<T> {
     pub fn for_state(state: T) -> Self {
         Self {
-            mystate: state
+            mystate: state,
+            presentation: true,
+            seed: None,
+            config: None,
+            replay: None,
+            record: None,
         }
     }
+
+    /// Play this replay file instead of reading the player's input, it brings its own seed and config.
+    pub fn with_replay(mut self, path: Option<std::path::PathBuf>) -> Self {
+        self.replay = path;
+        self
+    }
+
+    /// Write a replay of every game to this file.
+    pub fn with_record(mut self, path: Option<std::path::PathBuf>) -> Self {
+        self.record = path;
+        self
+    }
+
+    /// Load the tuning values from this file instead of `assets/invaders.ron`.
+    pub fn with_config(mut self, path: Option<std::path::PathBuf>) -> Self {
+        self.config = path;
+        self
+    }
+
+    /// Play every game with the same seed instead of a fresh random one.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Run the game logic only: no meshes, menus or hud, so it works under `MinimalPlugins`.
+    pub fn headless(mut self) -> Self {
+        self.presentation = false;
+        self
+    }
 }
 
 impl<T: States+Copy> Plugin for InvadersPlugin<T> {
     fn build(&self, app: &mut App) {
+        let (config, seed) = match &self.replay {
+            Some(path) => {
+                let replay = Replay::load(path).unwrap_or_else(|e| panic!("{}", e));
+                let result = (replay.config.clone(), Some(replay.seed));
+                app.insert_resource(Playback::new(replay));
+                result
+            }
+            None => (load_config(self.config.as_deref()), self.seed),
+        };
+        if let Some(path) = &self.record {
+            app.insert_resource(Recorder::new(path.clone()));
+        }
+
         app
             .add_event::<ExitEvent>()
+            .add_event::<SaveEvent>()
+            .add_event::<PlaySound>()
 
-            .insert_resource(InvadersGame {
-                drag: 1.0,
-                f: 100.0,
-                shoot_delay: 1.5,
-                ..default()
-            })
+            .insert_resource(config.arena.clone())
+            .insert_resource(config)
+            .init_resource::<InvadersGame>()
+            .insert_resource(InvadersRng::new(seed))
+            .init_resource::<PlayerInput>()
 
             .insert_state(InvaderState::None)
 
+            .init_resource::<GameClock>()
+
+            .register_type::<InvadersGame>()
+            .register_type::<GameClock>()
+            .register_type::<RngState>()
+            .register_type::<MInvaders>()
+            .register_type::<Transform>()
+            .register_type::<Parent>()
+            .register_type::<Children>()
+            .register_type_data::<Transform, ReflectSave>()
+            .register_type_data::<Parent, ReflectSave>()
+            .register_type_data::<Children, ReflectSave>()
+
+            .configure_sets(FixedUpdate, (
+                InvadersSet::Clock,
+                InvadersSet::ReadInput,
+                InvadersSet::Input,
+                InvadersSet::Move,
+                InvadersSet::Detect,
+                InvadersSet::Collide,
+                InvadersSet::Resolve,
+            ).chain().run_if(in_state(InvaderState::Game).and_then(no_pending_state)))
+
             .add_systems(OnEnter(self.mystate), invaders_setup)
             .add_systems(OnEnter(InvaderState::Game), clear_input)
-            .add_systems(OnExit(self.mystate), invaders_exit)
-            .add_systems(PreUpdate, invaders_key_input.run_if(in_state(InvaderState::Game)))
+            .add_systems(OnExit(self.mystate), (invaders_exit, replay::save_replay))
+            .add_systems(OnEnter(InvaderState::Start), replay::replay_start)
+            .add_systems(OnEnter(InvaderState::Start), save::load_game.run_if(resource_exists::<ResumeGame>))
+            .add_systems(Update, save::save_game.run_if(on_event::<SaveEvent>()))
+            .add_systems(OnEnter(InvaderState::Win), (replay::save_replay, play_end_sound))
+            .add_systems(OnEnter(InvaderState::Gameover), (replay::save_replay, play_end_sound))
+            .add_systems(OnEnter(InvaderState::Pause), pause_sounds(true))
+            .add_systems(OnExit(InvaderState::Pause), pause_sounds(false))
+            .add_systems(PreUpdate, invaders_key_input.after(ActionsSystem).run_if(in_state(InvaderState::Game)))
             .add_systems(Update, invaders_exit_event.run_if(in_state(InvaderState::Game)))
-            .add_systems(Update, reduce_score.run_if(in_state(InvaderState::Game)))
-            .add_systems(Update, count_time.run_if(in_state(InvaderState::Game)))
-
-            .add_plugins(BatPlugin::for_state(InvaderState::Game))
-            .add_plugins(AliensPlugin::for_state(InvaderState::Game))
-            .add_plugins(BulletPlugin)
-            .add_plugins(MenuPlugin)
-            .add_plugins(HudPlugin)
+            .add_systems(FixedUpdate, tick_clock.in_set(InvadersSet::Clock))
+            .add_systems(FixedUpdate, (
+                input::read_input.run_if(not(replay::playing)),
+                replay::play_input.run_if(replay::playing),
+                replay::record_input,
+            ).chain().in_set(InvadersSet::ReadInput))
+            .add_systems(FixedUpdate, (reduce_score, count_time).in_set(InvadersSet::Move))
+            .add_systems(FixedUpdate, score_events.in_set(InvadersSet::Resolve))
+
+            .add_plugins(BatPlugin::for_state(InvaderState::Game).presentation(self.presentation))
+            .add_plugins(AliensPlugin::for_state(InvaderState::Game).presentation(self.presentation))
+            .add_plugins(SaucerPlugin::for_state(InvaderState::Game).presentation(self.presentation))
+            .add_plugins(BunkerPlugin::for_state(InvaderState::Game).presentation(self.presentation))
+            .add_plugins(BulletPlugin { presentation: self.presentation })
+            .add_plugins(MenuPlugin { presentation: self.presentation })
+            .add_plugins(InterpolationPlugin)
+            .add_plugins(EventsPlugin)
+            .add_plugins(CollisionPlugin)
         ;
+
+        if self.presentation {
+            app
+                .add_plugins(HudPlugin)
+                .add_plugins(TouchPlugin)
+                .add_systems(Startup, fit_window);
+        } else {
+            // MinimalPlugins lacks these, DefaultPlugins already has them
+            if !app.is_plugin_added::<TransformPlugin>() {
+                app.add_plugins(TransformPlugin);
+            }
+            if !app.is_plugin_added::<HierarchyPlugin>() {
+                app.add_plugins(HierarchyPlugin);
+            }
+            if !app.is_plugin_added::<InputPlugin>() {
+                app.add_plugins(InputPlugin);
+            }
+            if !app.is_plugin_added::<ActionsPlugin>() {
+                app.add_plugins(ActionsPlugin);
+            }
+            app
+                .add_systems(OnEnter(InvaderState::Start), headless_start)
+                .add_systems(OnEnter(InvaderState::Win), headless_finish)
+                .add_systems(OnEnter(InvaderState::Gameover), headless_finish)
+            ;
+        }
     }
 }
 
-#[derive(Resource, Default, Debug)]
+/// How a headless game ended, inserted once it is decided.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct HeadlessResult {
+    pub won: bool,
+    pub wave: u32,
+    pub score: i32,
+    /// Fixed ticks the game lasted.
+    pub ticks: u32,
+    pub seed: u64,
+}
+
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource, Save)]
 struct InvadersGame {
-    drag: f32,
-    f: f32,
-    shoot_delay: f32,
     score: i32,
     time: f32,
+    wave: u32,
+    lives: u32,
+    extra_lives: usize,
+    ticks: u32,
+    last_penalty: f32,
+    last_alien_shot: f32,
+    /// Game time of the next saucer, 0 until it is scheduled.
+    next_saucer: f32,
 }
 
+/// The only source of randomness in the game, reseeded when a game starts.
+#[derive(Resource, Deref, DerefMut)]
+struct InvadersRng {
+    fixed_seed: Option<u64>,
+    seed: u64,
+    #[deref]
+    rng: ChaCha12Rng,
+}
+
+impl InvadersRng {
+    fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or_else(rand::random);
+        Self {
+            fixed_seed,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    fn reseed(&mut self) {
+        *self = Self::new(self.fixed_seed);
+    }
+
+    fn state(&self) -> RngState {
+        RngState {
+            seed: self.seed,
+            word_pos: self.rng.get_word_pos() as u64,
+        }
+    }
+
+    /// Continues the stream of a saved game, the next draws are the ones it would have made.
+    fn restore(&mut self, state: &RngState) {
+        self.seed = state.seed;
+        self.rng = ChaCha12Rng::seed_from_u64(state.seed);
+        self.rng.set_word_pos(state.word_pos as u128);
+    }
+}
+
+/// How far `InvadersRng` got, only there while a game is saved or loaded.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource, Save)]
+struct RngState {
+    seed: u64,
+    word_pos: u64,
+}
+
+/// Game time that stands still outside of `InvaderState::Game`, all game timers use it.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource, Save)]
+struct GameClock {
+    elapsed: f32,
+    delta: f32,
+}
 
 #[derive(Event)]
 struct ExitEvent;
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Save)]
 struct MInvaders;
 
+/// Order of the gameplay systems within one `FixedUpdate` tick.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+enum InvadersSet {
+    Clock,
+    ReadInput,
+    Input,
+    Move,
+    /// Finding the collisions the `Collide` systems react to.
+    Detect,
+    Collide,
+    Resolve,
+}
+
 #[derive(Component, States, Clone, PartialEq, Eq, Hash, Debug, Copy)]
 enum InvaderState {
     None,
@@ -84,28 +321,96 @@ enum InvaderState {
     Gameover,
 }
 
+#[cfg(not(target_family = "wasm"))]
+fn load_config(path: Option<&std::path::Path>) -> InvadersConfig {
+    let default_path = bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join("assets")
+        .join(config::CONFIG_FILE);
+    InvadersConfig::load(path.unwrap_or(&default_path)).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[cfg(target_family = "wasm")]
+fn load_config(_path: Option<&std::path::Path>) -> InvadersConfig {
+    InvadersConfig::parse(include_str!("../../assets/invaders.ron"))
+        .unwrap_or_else(|e| panic!("{}: {}", config::CONFIG_FILE, e))
+}
+
 fn invaders_setup(
     mut camera: Query<(&mut OrthographicProjection, &mut Transform)>,
     mut invaders_state: ResMut<NextState<InvaderState>>,
     mut game: ResMut<InvadersGame>,
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
+    mut rng: ResMut<InvadersRng>,
+    debug_info: Option<ResMut<DebugInfo>>,
 ) {
-    let (mut projection, mut cam_trans) = camera.single_mut();
-    projection.scaling_mode = ScalingMode::AutoMin {min_width: 1280.0, min_height: 840.0};
-    cam_trans.translation = Vec3::ZERO;
+    if let Ok((mut projection, mut cam_trans)) = camera.get_single_mut() {
+        projection.scaling_mode = ScalingMode::AutoMin {min_width: arena.width, min_height: arena.height};
+        cam_trans.translation = Vec3::ZERO;
+    }
 
-    game.score = 0;
-    game.time = 0.0;
+    *game = InvadersGame {
+        wave: 1,
+        lives: config.lives.start,
+        ..default()
+    };
+
+    rng.reseed();
+    info!("invaders seed {}", rng.seed);
+    if let Some(mut debug_info) = debug_info {
+        debug_info.0 = format!("Seed: {}", rng.seed);
+    }
 
     invaders_state.set(InvaderState::Start);
 }
 
+/// Sizes the window to the arena, it can't be resized.
+fn fit_window(
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    arena: Res<Arena>,
+) {
+    for mut window in windows.iter_mut() {
+        window.resolution.set(arena.width, arena.height);
+        window.resize_constraints = WindowResizeConstraints {
+            min_width: arena.width,
+            min_height: arena.height,
+            max_width: arena.width,
+            max_height: arena.height,
+        };
+    }
+}
+
+fn headless_start(mut state: ResMut<NextState<InvaderState>>) {
+    state.set(InvaderState::Game);
+}
+
+fn headless_finish(
+    mut commands: Commands,
+    game: Res<InvadersGame>,
+    rng: Res<InvadersRng>,
+    state: Res<State<InvaderState>>,
+    mut main_state: ResMut<NextState<MainState>>,
+    mut inv_state: ResMut<NextState<InvaderState>>,
+) {
+    info!("{:?}: wave {}, score {}, time {:.1}, seed {}", state.get(), game.wave, game.score, game.time, rng.seed);
+    commands.insert_resource(HeadlessResult {
+        won: *state.get() == InvaderState::Win,
+        wave: game.wave,
+        score: game.score,
+        ticks: game.ticks,
+        seed: rng.seed,
+    });
+    main_state.set(MainState::MainMenu);
+    inv_state.set(InvaderState::None);
+}
+
 fn invaders_key_input(
-    mut input: ResMut<ButtonInput<KeyCode>>,
+    mut actions: ResMut<ButtonInput<Action>>,
     mut state: ResMut<NextState<InvaderState>>,
 ) {
-    if input.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Pause) {
         state.set(InvaderState::Pause);
-        input.clear_just_pressed(KeyCode::Escape);
+        actions.clear_just_pressed(Action::Pause);
         // println!("input {:?}", i);
     }
 }
@@ -121,35 +426,81 @@ fn invaders_exit_event(
     }
 }
 
-fn invaders_exit(mut commands: Commands, mut q: Query<Entity, With<MInvaders>>) {
+fn invaders_exit(mut commands: Commands, mut q: Query<Entity, With<MInvaders>>, mut clock: ResMut<GameClock>) {
     for entity in q.iter_mut() {
         commands.entity(entity).despawn_recursive();
     }
+    *clock = default();
+}
+
+fn play_end_sound(state: Res<State<InvaderState>>, mut sound: EventWriter<PlaySound>) {
+    sound.send(PlaySound(if *state.get() == InvaderState::Win { Sfx::Win } else { Sfx::Gameover }));
+}
+
+fn pause_sounds(pause: bool) -> impl Fn(Option<ResMut<SoundPaused>>) {
+    move |paused| {
+        if let Some(mut paused) = paused {
+            paused.0 = pause;
+        }
+    }
+}
+
+/// Stops the simulation in the tick that decided to leave the game, not a few ticks later.
+fn no_pending_state(next: Res<NextState<InvaderState>>) -> bool {
+    next.0.is_none()
+}
+
+fn tick_clock(
+    mut clock: ResMut<GameClock>,
+    time: Res<Time>,
+) {
+    clock.delta = time.delta_seconds();
+    clock.elapsed += clock.delta;
 }
 
 fn reduce_score(
     mut game: ResMut<InvadersGame>,
-    time: Res<Time>,
-    mut last_update: Local<f32>,
+    config: Res<InvadersConfig>,
+    clock: Res<GameClock>,
 ) {
-    let now = time.elapsed_seconds_wrapped();
-    if now - *last_update > 1.0 {
-        game.score = 0.max(game.score - 2);
-        *last_update = now;
+    let now = clock.elapsed;
+    if now - game.last_penalty > 1.0 {
+        game.score = 0.max(game.score + config.score.per_second);
+        game.last_penalty = now;
+    }
+}
+
+/// Points for kills, the penalty for the player's shots.
+fn score_events(
+    mut game: ResMut<InvadersGame>,
+    config: Res<InvadersConfig>,
+    mut killed: EventReader<AlienKilled>,
+    mut shots: EventReader<ShotFired>,
+) {
+    for kill in killed.read() {
+        game.score += kill.points;
+    }
+    for _ in shots.read().filter(|shot| !shot.alien) {
+        game.score = 0.max(game.score + config.score.shot);
     }
 }
 
 fn clear_input(
     mut key: ResMut<ButtonInput<KeyCode>>,
-    mut mouse: ResMut<ButtonInput<MouseButton>>
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    mut pad: ResMut<ButtonInput<GamepadButton>>,
+    mut actions: ResMut<ButtonInput<Action>>,
 ) {
     key.reset_all();
     mouse.reset_all();
+    pad.reset_all();
+    actions.reset_all();
 }
 
 fn count_time(
     mut game: ResMut<InvadersGame>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
 ) {
-    game.time += time.delta_seconds();
+    game.time += clock.delta;
+    game.ticks += 1;
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+/// Rows at the bottom that change between the two frames.
+const LEG_ROWS: usize = 2;
+
+/// Seed of the art for a row of a wave, from `pixel_art.seed` of the config.
+pub fn art_seed(seed: u64, wave: u32, row: u32) -> u64 {
+    seed.wrapping_add((wave as u64) << 16 | row as u64)
+}
+
+/// A `size` x `size` invader grown from `seed`, two march frames side by side like the sprite art.
+///
+/// ChaCha rather than `StdRng`, whose algorithm may change with rand, so a seed keeps its looks.
+pub fn alien_image(seed: u64, size: usize, color: Color) -> Image {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    let body = loop {
+        let mask = alien_mask(&mut rng, size);
+        if mask.iter().filter(|p| **p).count() >= size * size / 3 {
+            break mask;
+        }
+    };
+    let mut legs = alien_mask(&mut rng, size);
+    legs[..size * (size - LEG_ROWS)].copy_from_slice(&body[..size * (size - LEG_ROWS)]);
+
+    let pixel = color.as_rgba_u8();
+    let mut data = vec![0; 2 * size * size * 4];
+    for (frame, mask) in [&body, &legs].into_iter().enumerate() {
+        for y in 0..size {
+            for x in 0..size {
+                if mask[y * size + x] {
+                    let i = (y * 2 * size + frame * size + x) * 4;
+                    data[i..i + 4].copy_from_slice(&pixel);
+                }
+            }
+        }
+    }
+    let mut image = Image::new(
+        Extent3d { width: 2 * size as u32, height: size as u32, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+/// Random left half mirrored to the right, denser towards the middle column.
+fn alien_mask(rng: &mut ChaCha12Rng, size: usize) -> Vec<bool> {
+    let half = size.div_ceil(2);
+    let mut mask = vec![false; size * size];
+    for y in 0..size {
+        for x in 0..half {
+            let centre = (x + 1) as f32 / half as f32;
+            let on = rng.gen::<f32>() < 0.25 + 0.35 * centre;
+            mask[y * size + x] = on;
+            mask[y * size + size - 1 - x] = on;
+        }
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(wave: u32, row: u32) -> Vec<u8> {
+        alien_image(art_seed(1978, wave, row), 8, Color::WHITE).data
+    }
+
+    #[test]
+    fn same_seed_row_and_wave_look_the_same() {
+        assert_eq!(pixels(1, 2), pixels(1, 2));
+    }
+
+    #[test]
+    fn waves_look_different() {
+        assert_ne!(pixels(1, 2), pixels(2, 2));
+        assert_ne!(pixels(1, 2), pixels(1, 3));
+    }
+}
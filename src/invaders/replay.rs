@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::invaders::config::InvadersConfig;
+use crate::invaders::input::PlayerInput;
+use crate::invaders::{InvadersGame, InvadersRng};
+
+/// Everything needed to play a game again: seed, config and the input changes.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: InvadersConfig,
+    /// Fixed tick of the game and `PlayerInput` bits from that tick on.
+    pub inputs: Vec<(u32, u16)>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let replay: Self = ron::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        replay.config.validate().map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) {
+        let result = ron::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|text| std::fs::write(path, text).map_err(|e| e.to_string()));
+        match result {
+            Ok(_) => info!("replay saved to {}", path.display()),
+            Err(e) => error!("unable to save replay to {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Feeds a loaded replay to the game instead of the player's input.
+#[derive(Resource)]
+pub struct Playback {
+    replay: Replay,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, next: 0 }
+    }
+}
+
+/// Collects the input of the current game and writes it to `path`.
+#[derive(Resource)]
+pub struct Recorder {
+    path: PathBuf,
+    replay: Option<Replay>,
+    last: Option<PlayerInput>,
+}
+
+impl Recorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, replay: None, last: None }
+    }
+}
+
+pub fn playing(playback: Option<Res<Playback>>) -> bool {
+    playback.is_some()
+}
+
+pub fn replay_start(
+    playback: Option<ResMut<Playback>>,
+    recorder: Option<ResMut<Recorder>>,
+    rng: Res<InvadersRng>,
+    config: Res<InvadersConfig>,
+) {
+    if let Some(mut playback) = playback {
+        playback.next = 0;
+    }
+    if let Some(mut recorder) = recorder {
+        recorder.replay = Some(Replay {
+            seed: rng.seed,
+            config: config.clone(),
+            inputs: vec![],
+        });
+        recorder.last = None;
+    }
+}
+
+pub fn play_input(
+    mut playback: ResMut<Playback>,
+    game: Res<InvadersGame>,
+    mut input: ResMut<PlayerInput>,
+) {
+    while let Some(&(tick, bits)) = playback.replay.inputs.get(playback.next) {
+        if tick > game.ticks {
+            break;
+        }
+        *input = PlayerInput::from_bits(bits);
+        playback.next += 1;
+    }
+}
+
+pub fn record_input(
+    recorder: Option<ResMut<Recorder>>,
+    game: Res<InvadersGame>,
+    input: Res<PlayerInput>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    if recorder.last == Some(*input) {
+        return;
+    }
+    recorder.last = Some(*input);
+    if let Some(replay) = &mut recorder.replay {
+        replay.inputs.push((game.ticks, input.to_bits()));
+    }
+}
+
+pub fn save_replay(recorder: Option<ResMut<Recorder>>) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    if let Some(replay) = recorder.replay.take() {
+        replay.save(&recorder.path);
+    }
+}
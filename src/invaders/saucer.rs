@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use bevy::ecs::system::EntityCommands;
+use bevy::sprite::Mesh2dHandle;
+use rand::Rng;
+
+use crate::invaders::config::{Arena, InvadersConfig};
+use crate::invaders::{GameClock, InvadersGame, InvadersRng, InvadersSet, MInvaders};
+use crate::invaders::events::AlienKilled;
+use crate::invaders::aliens::{self, MAlien};
+use crate::invaders::collision::{Collision, Hitbox, Layer};
+use crate::invaders::interpolation::Interpolated;
+use crate::invaders::save::{self, ReflectSave, SaveRestore};
+use crate::MainState;
+
+/// The mystery ship crossing the top of the arena now and then, worth a random bonus.
+pub struct SaucerPlugin<T: States+Copy> {
+    mystate: T,
+    presentation: bool,
+}
+
+impl<T:States+Copy> SaucerPlugin<T> {
+    pub fn for_state(state: T) -> Self {
+        Self {
+            mystate: state,
+            presentation: true,
+        }
+    }
+
+    pub fn presentation(mut self, presentation: bool) -> Self {
+        self.presentation = presentation;
+        self
+    }
+}
+
+impl<T:States+Copy> Plugin for SaucerPlugin<T> {
+    fn build(&self, app: &mut App) {
+        if self.presentation {
+            app.init_resource::<SaucerAssets>();
+        }
+        app
+            .register_type::<MSaucer>()
+            .add_systems(FixedUpdate, saucer_move.in_set(InvadersSet::Move).run_if(in_state(self.mystate)))
+            .add_systems(FixedUpdate, check_shot.in_set(InvadersSet::Collide).run_if(in_state(self.mystate)))
+            // both draw from InvadersRng, a fixed order keeps seeded games the same
+            .add_systems(FixedUpdate, saucer_spawn.after(aliens::shoot).in_set(InvadersSet::Resolve).run_if(in_state(self.mystate)))
+            .add_systems(Update, save::restore_saved::<MSaucer>.run_if(in_state(MainState::Invaders)))
+        ;
+    }
+}
+
+const SAUCER_SIZE: Vec2 = Vec2::new(60.0, 20.0);
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component, Save)]
+struct MSaucer {
+    v: f32,
+}
+
+#[derive(Resource)]
+pub(super) struct SaucerAssets {
+    mesh: Handle<Mesh>,
+    mat: Handle<ColorMaterial>,
+}
+
+impl FromWorld for SaucerAssets {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            mesh: world.resource_mut::<Assets<Mesh>>().add(Circle::new(0.5)),
+            mat: world.resource_mut::<Assets<ColorMaterial>>().add(Color::rgb_u8(224, 110, 160)),
+        }
+    }
+}
+
+impl SaveRestore for MSaucer {
+    type Assets = SaucerAssets;
+
+    fn restore(&self, saucer: &mut EntityCommands, assets: &SaucerAssets) {
+        saucer.insert((Mesh2dHandle(assets.mesh.clone()), assets.mat.clone()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn saucer_spawn(
+    mut commands: Commands,
+    qsaucer: Query<(), With<MSaucer>>,
+    qaliens: Query<(), With<MAlien>>,
+    mut game: ResMut<InvadersGame>,
+    mut rng: ResMut<InvadersRng>,
+    clock: Res<GameClock>,
+    config: Res<InvadersConfig>,
+    arena: Res<Arena>,
+    assets: Option<Res<SaucerAssets>>,
+) {
+    if !qsaucer.is_empty() {
+        return;
+    }
+    let config = &config.saucer;
+    let now = clock.elapsed;
+    if game.next_saucer == 0.0 {
+        game.next_saucer = now + rng.gen_range(config.min_delay..=config.max_delay);
+        return;
+    }
+    if now < game.next_saucer {
+        return;
+    }
+    game.next_saucer = 0.0;
+    if (qaliens.iter().count() as u32) < config.min_aliens {
+        return;
+    }
+
+    let dir = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+    let pos = Vec3::new(-dir * (arena.right() + SAUCER_SIZE.x / 2.0), arena.saucer_height(), 0.0);
+    let mut saucer = commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(pos).with_scale(SAUCER_SIZE.extend(1.0))),
+        Interpolated::at(pos),
+        Hitbox::new(SAUCER_SIZE, Layer::Alien),
+        MInvaders,
+        MSaucer { v: dir * config.speed },
+    ));
+    if let Some(assets) = assets {
+        saucer.insert((Mesh2dHandle(assets.mesh.clone()), assets.mat.clone()));
+    }
+}
+
+/// Moves the saucer across and lets it go once it left the other side.
+fn saucer_move(
+    mut commands: Commands,
+    mut qsaucer: Query<(Entity, &MSaucer, &mut Transform)>,
+    clock: Res<GameClock>,
+    arena: Res<Arena>,
+) {
+    for (e, saucer, mut t) in qsaucer.iter_mut() {
+        t.translation.x += saucer.v * clock.delta;
+        if t.translation.x.abs() > arena.right() + SAUCER_SIZE.x / 2.0 {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+}
+
+fn check_shot(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    qsaucer: Query<&Transform, With<MSaucer>>,
+    mut rng: ResMut<InvadersRng>,
+    config: Res<InvadersConfig>,
+    mut killed: EventWriter<AlienKilled>,
+) {
+    let mut dead = Vec::new();
+    for c in collisions.read().filter(|c| c.is(Layer::PlayerBullet, Layer::Alien)) {
+        let Ok(st) = qsaucer.get(c.b) else {
+            continue;
+        };
+        if dead.contains(&c.b) {
+            continue;
+        }
+        dead.push(c.b);
+        commands.entity(c.a).despawn();
+        commands.entity(c.b).despawn_recursive();
+        let points = &config.saucer.points;
+        killed.send(AlienKilled {
+            pos: st.translation.xy(),
+            points: points[rng.gen_range(0..points.len())],
+            saucer: true,
+        });
+    }
+}
\ No newline at end of file
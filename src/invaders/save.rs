@@ -0,0 +1,132 @@
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::reflect::FromType;
+use bevy::scene::serde::SceneDeserializer;
+use bevy::scene::{DynamicSceneBuilder, SceneFilter};
+use serde::de::DeserializeSeed;
+
+use crate::invaders::{InvadersRng, MInvaders, RngState};
+use crate::storage;
+
+const STORAGE_KEY: &str = "savegame.scn.ron";
+
+/// Components and resources with `#[reflect(Save)]` are written to a saved game.
+///
+/// Everything else is rebuilt by the plugins when the game is resumed, meshes for example.
+#[derive(Clone)]
+pub struct ReflectSave;
+
+impl<T> FromType<T> for ReflectSave {
+    fn from_type() -> Self {
+        ReflectSave
+    }
+}
+
+/// Insert before entering the Invaders state to continue the saved game instead of starting a new one.
+#[derive(Resource)]
+pub struct ResumeGame;
+
+#[derive(Event)]
+pub struct SaveEvent;
+
+/// The part of a saved entity that is rebuilt instead of saved, the transform bundle and with
+/// presentation the looks from `Assets`.
+///
+/// Plugins run `restore_saved::<T>` for their saved components, it finds the entities a load
+/// brought back by their missing `GlobalTransform`.
+pub(super) trait SaveRestore: Component {
+    type Assets: Resource;
+
+    fn restore(&self, _entity: &mut EntityCommands, _assets: &Self::Assets) {}
+}
+
+pub(super) fn restore_saved<T: SaveRestore>(
+    mut commands: Commands,
+    q: Query<(Entity, &Transform, &T), Without<GlobalTransform>>,
+    assets: Option<Res<T::Assets>>,
+) {
+    for (entity, transform, saved) in q.iter() {
+        let mut entity = commands.entity(entity);
+        entity.insert(SpatialBundle::from_transform(*transform));
+        if let Some(assets) = &assets {
+            saved.restore(&mut entity, assets);
+        }
+    }
+}
+
+pub fn has_saved_game() -> bool {
+    storage::read(STORAGE_KEY).is_some()
+}
+
+fn save_filter(world: &World) -> SceneFilter {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    registry.iter()
+        .filter(|r| r.data::<ReflectSave>().is_some())
+        .fold(SceneFilter::deny_all(), |f, r| f.allow_by_id(r.type_id()))
+}
+
+pub fn save_game(world: &mut World) {
+    match save_to_text(world) {
+        Ok(text) => storage::write(STORAGE_KEY, &text),
+        Err(e) => error!("unable to save game: {}", e),
+    }
+}
+
+/// Replaces the freshly set up game with the saved one, then forgets the save.
+pub fn load_game(world: &mut World) {
+    world.remove_resource::<ResumeGame>();
+    let Some(text) = storage::read(STORAGE_KEY) else {
+        return;
+    };
+    storage::remove(STORAGE_KEY);
+    if let Err(e) = load_from_text(world, &text) {
+        error!("unable to restore saved game: {}", e);
+    }
+}
+
+/// The running game as scene text, what `save_game` stores.
+pub fn save_to_text(world: &mut World) -> Result<String, String> {
+    let mut stack: Vec<Entity> = world.query_filtered::<Entity, With<MInvaders>>().iter(world).collect();
+    let mut entities = Vec::new();
+    let mut qchildren = world.query::<&Children>();
+    while let Some(entity) = stack.pop() {
+        entities.push(entity);
+        if let Ok(children) = qchildren.get(world, entity) {
+            stack.extend(children.iter());
+        }
+    }
+
+    // the rng can't be reflected, its position is saved instead
+    let rng_state = world.resource::<InvadersRng>().state();
+    world.insert_resource(rng_state);
+    let filter = save_filter(world);
+    let scene = DynamicSceneBuilder::from_world(world)
+        .with_filter(filter.clone())
+        .with_resource_filter(filter)
+        .extract_entities(entities.into_iter())
+        .extract_resources()
+        .build();
+    world.remove_resource::<RngState>();
+    scene.serialize_ron(world.resource::<AppTypeRegistry>()).map_err(|e| e.to_string())
+}
+
+/// Replaces the running game with one from `save_to_text`.
+pub fn load_from_text(world: &mut World, text: &str) -> Result<(), String> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let scene = {
+        let registry = registry.read();
+        let mut de = ron::Deserializer::from_str(text).map_err(|e| e.to_string())?;
+        SceneDeserializer { type_registry: &registry }.deserialize(&mut de).map_err(|e| e.to_string())?
+    };
+
+    let fresh: Vec<Entity> = world.query_filtered::<Entity, With<MInvaders>>().iter(world).collect();
+    for entity in fresh {
+        despawn_with_children_recursive(world, entity);
+    }
+    scene.write_to_world(world, &mut EntityHashMap::default()).map_err(|e| e.to_string())?;
+    if let Some(rng_state) = world.remove_resource::<RngState>() {
+        world.resource_mut::<InvadersRng>().restore(&rng_state);
+    }
+    Ok(())
+}
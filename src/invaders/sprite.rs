@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use bevy::render::texture::{ImageLoaderSettings, ImageSampler};
+
+/// Art for one kind of entity: `frames` pictures of the same size side by side in one image.
+///
+/// Entities keep their mesh until the image is loaded, so missing art falls back to the shapes.
+pub struct SpriteSheet {
+    image: Handle<Image>,
+    frames: usize,
+    layout: Option<Handle<TextureAtlasLayout>>,
+}
+
+impl SpriteSheet {
+    /// Starts loading `assets/<path>`, `None` if there is no such file.
+    pub fn load(assets: &AssetServer, path: &str, frames: usize) -> Option<Self> {
+        #[cfg(not(target_family = "wasm"))]
+        if !bevy::asset::io::file::FileAssetReader::get_base_path().join("assets").join(path).is_file() {
+            return None;
+        }
+        let image = assets.load_with_settings(path.to_owned(), |s: &mut ImageLoaderSettings| {
+            s.sampler = ImageSampler::nearest();
+        });
+        Some(Self::from_image(image, frames))
+    }
+
+    pub fn from_image(image: Handle<Image>, frames: usize) -> Self {
+        Self { image, frames, layout: None }
+    }
+
+    /// Sprite components of `size` showing `frame`, `None` until the image is loaded.
+    pub fn sprite(
+        &mut self,
+        frame: usize,
+        size: Vec2,
+        images: &Assets<Image>,
+        layouts: &mut Assets<TextureAtlasLayout>,
+    ) -> Option<(Sprite, Handle<Image>, TextureAtlas)> {
+        if self.layout.is_none() {
+            let image = images.get(&self.image)?;
+            let tile = Vec2::new(image.width() as f32 / self.frames as f32, image.height() as f32);
+            self.layout = Some(layouts.add(TextureAtlasLayout::from_grid(tile, self.frames, 1, None, None)));
+        }
+        Some((
+            Sprite { custom_size: Some(size), ..default() },
+            self.image.clone(),
+            TextureAtlas { layout: self.layout.clone()?, index: frame % self.frames },
+        ))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,143 @@
+use bevy::app::{App, Plugin};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionsSystem, VirtualActions};
+use crate::invaders::InvaderState;
+use crate::MainState;
+
+/// On-screen buttons for phones and tablets: move left and right, fire and pause.
+///
+/// They stay hidden until the first touch, menus need nothing extra since UI focus handles taps.
+/// Every finger counts, so the bat can move and fire at the same time.
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TouchControls>()
+            .add_systems(OnEnter(MainState::Invaders), create_touch_zones)
+            .add_systems(OnExit(MainState::Invaders), destroy_touch_zones)
+            .add_systems(PreUpdate, touch_input.after(InputSystem).before(ActionsSystem))
+            .add_systems(Update, show_touch_zones)
+        ;
+    }
+}
+
+const ZONE_COLOR: Color = Color::rgba(155.0 / 255.0, 176.0 / 255.0, 193.0 / 255.0, 64.0 / 255.0);
+const ZONE_PRESSED_COLOR: Color = Color::rgba(155.0 / 255.0, 176.0 / 255.0, 193.0 / 255.0, 160.0 / 255.0);
+
+/// Whether the screen was ever touched, the zones show from then on.
+#[derive(Resource, Default)]
+struct TouchControls {
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct MTouchZones;
+
+/// Holds the action while a finger is on it.
+#[derive(Component)]
+struct MTouchZone(Action);
+
+fn create_touch_zones(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    let Some(font) = assets.get_handle("eight-bit-dragon.otf") else {
+        error!("menu font not loaded");
+        return;
+    };
+    let text_style = TextStyle {
+        font,
+        font_size: 40.0,
+        color: Color::rgb_u8(81, 130, 155),
+    };
+    let zone = |action, label, position: Style| (action, label, Style {
+        position_type: PositionType::Absolute,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..position
+    });
+    let zones = [
+        zone(Action::MoveLeft, "<", Style { left: Val::Percent(2.0), bottom: Val::Percent(3.0), width: Val::Percent(14.0), height: Val::Percent(20.0), ..default() }),
+        zone(Action::MoveRight, ">", Style { left: Val::Percent(18.0), bottom: Val::Percent(3.0), width: Val::Percent(14.0), height: Val::Percent(20.0), ..default() }),
+        zone(Action::Fire, "Fire", Style { right: Val::Percent(2.0), bottom: Val::Percent(3.0), width: Val::Percent(20.0), height: Val::Percent(20.0), ..default() }),
+        zone(Action::Pause, "Pause", Style { right: Val::Percent(2.0), top: Val::Percent(2.0), width: Val::Percent(14.0), height: Val::Percent(8.0), ..default() }),
+    ];
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        MTouchZones,
+    )).with_children(|commands| {
+        for (action, label, style) in zones {
+            commands.spawn((
+                NodeBundle {
+                    style,
+                    background_color: BackgroundColor(ZONE_COLOR),
+                    ..default()
+                },
+                MTouchZone(action),
+            )).with_children(|commands| {
+                commands.spawn(TextBundle::from_section(label, text_style.clone()));
+            });
+        }
+    });
+}
+
+fn destroy_touch_zones(
+    mut commands: Commands,
+    q: Query<Entity, With<MTouchZones>>,
+) {
+    for e in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn touch_input(
+    touches: Res<Touches>,
+    mut controls: ResMut<TouchControls>,
+    qzones: Query<(&MTouchZone, &Node, &GlobalTransform, &InheritedVisibility)>,
+    mut virtual_actions: ResMut<VirtualActions>,
+) {
+    if !controls.enabled && touches.any_just_pressed() {
+        info!("touch screen detected, showing touch controls");
+        controls.enabled = true;
+    }
+    virtual_actions.0.clear();
+    for (MTouchZone(action), node, transform, visibility) in qzones.iter() {
+        let rect = node.logical_rect(transform);
+        if visibility.get() && touches.iter().any(|touch| rect.contains(touch.position())) {
+            virtual_actions.0.insert(*action);
+        }
+    }
+}
+
+/// Shows the zones while the game runs, menus take taps instead.
+fn show_touch_zones(
+    controls: Res<TouchControls>,
+    state: Res<State<InvaderState>>,
+    virtual_actions: Res<VirtualActions>,
+    mut qzones: Query<&mut Visibility, With<MTouchZones>>,
+    mut qzone: Query<(&MTouchZone, &mut BackgroundColor)>,
+) {
+    let Ok(mut visibility) = qzones.get_single_mut() else {
+        return;
+    };
+    let show = controls.enabled && *state.get() == InvaderState::Game;
+    visibility.set_if_neq(if show { Visibility::Inherited } else { Visibility::Hidden });
+    for (MTouchZone(action), mut color) in qzone.iter_mut() {
+        let new_color = if virtual_actions.0.contains(action) { ZONE_PRESSED_COLOR } else { ZONE_COLOR };
+        if color.0 != new_color {
+            color.0 = new_color;
+        }
+    }
+}
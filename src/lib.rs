@@ -0,0 +1,52 @@
+//! The game's plugins, put together by `main.rs`. `headless_app` plays a game without window
+//! for `--headless` and the tests.
+
+use std::time::Duration;
+
+use bevy::app::App;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+use crate::invaders::InvadersPlugin;
+
+pub mod actions;
+pub mod controls_menu;
+pub mod focus;
+pub mod invaders;
+pub mod fps_counter;
+pub mod highscores;
+pub mod main_menu;
+pub mod sound;
+mod state_plugin;
+mod storage;
+
+/// Time every update of the headless app advances, one `FixedUpdate` tick at the default 64 Hz.
+pub const HEADLESS_STEP: Duration = Duration::from_micros(15_625);
+
+/// App that plays one game under `MinimalPlugins`, the game's result is then in `HeadlessResult`.
+///
+/// Updates advance a fixed `HEADLESS_STEP` instead of the wall clock, a game runs as fast as
+/// the machine allows and every run with the same seed and input ends the same way.
+pub fn headless_app(invaders: InvadersPlugin<MainState>) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins(MinimalPlugins)
+        .add_plugins(LogPlugin::default())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_STEP))
+        .insert_state(MainState::Invaders)
+        .add_plugins(invaders.headless())
+        .add_systems(OnEnter(MainState::MainMenu), headless_exit);
+    app
+}
+
+fn headless_exit(mut exit_event: EventWriter<bevy::app::AppExit>) {
+    exit_event.send(bevy::app::AppExit);
+}
+
+#[derive(Component, States, Clone, PartialEq, Eq, Hash, Debug, Copy)]
+pub enum MainState {
+    MainMenu,
+    Invaders,
+    Controls,
+}
@@ -3,14 +3,15 @@ use bevy::DefaultPlugins;
 use bevy::prelude::*;
 use bevy::window::{EnabledButtons, PresentMode};
 
-use crate::fps_counter::FpsCounterPlugin;
-use crate::invaders::InvadersPlugin;
-use crate::main_menu::MainMenuPlugin;
-
-mod invaders;
-mod fps_counter;
-mod main_menu;
-mod state_plugin;
+use invaders::actions::ActionsPlugin;
+use invaders::controls_menu::ControlsMenuPlugin;
+use invaders::focus::FocusPlugin;
+use invaders::fps_counter::FpsCounterPlugin;
+use invaders::highscores::HighScoresPlugin;
+use invaders::invaders::InvadersPlugin;
+use invaders::main_menu::MainMenuPlugin;
+use invaders::sound::SoundPlugin;
+use invaders::{headless_app, MainState};
 
 //palette https://colorhunt.co/palette/eadfb49bb0c151829bf6995c
 // rgb(234, 223, 180)
@@ -19,6 +20,21 @@ mod state_plugin;
 // rgb(246, 153, 92)
 
 fn main() {
+    let seed = arg_value("--seed").map(|s| s.parse::<u64>().expect("--seed must be a number"));
+    let config = arg_value("--config").map(std::path::PathBuf::from);
+    let replay = arg_value("--replay").map(std::path::PathBuf::from);
+    let record = arg_value("--record").map(std::path::PathBuf::from);
+
+    if std::env::args().any(|a| a == "--headless") {
+        headless_app(InvadersPlugin::for_state(MainState::Invaders)
+            .with_seed(seed)
+            .with_config(config)
+            .with_replay(replay)
+            .with_record(record))
+            .run();
+        return;
+    }
+
     App::new()
         .add_plugins(
             DefaultPlugins.set(
@@ -31,12 +47,6 @@ fn main() {
                             maximize: false,
                             close: true,
                         },
-                        resize_constraints: WindowResizeConstraints {
-                            min_width: 1280.0,
-                            min_height: 840.0,
-                            max_width: 1280.0,
-                            max_height: 840.0,
-                        },
                         title: "Invaders etc.".into(),
                         ..default()
                     }),
@@ -45,21 +55,30 @@ fn main() {
         )
         .add_systems(Startup, setup)
         .insert_state(MainState::MainMenu)
+        .add_plugins(ActionsPlugin)
+        .add_plugins(FocusPlugin)
         .add_plugins(FpsCounterPlugin::default())
+        .add_plugins(HighScoresPlugin)
+        .add_plugins(SoundPlugin)
         .add_plugins(MainMenuPlugin::for_state(MainState::MainMenu))
-        .add_plugins(InvadersPlugin::for_state(MainState::Invaders))
+        .add_plugins(ControlsMenuPlugin::for_state(MainState::Controls))
+        .add_plugins(InvadersPlugin::for_state(MainState::Invaders)
+            .with_seed(seed)
+            .with_config(config)
+            .with_replay(replay)
+            .with_record(record))
 
         .run();
 }
 
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|a| a != name);
+    args.next()?;
+    args.next()
+}
+
 fn setup(mut commands: Commands) {
     let mut cam_bundle = Camera2dBundle::default();
     cam_bundle.camera.clear_color = ClearColorConfig::Custom(Color::rgb_u8(234, 223, 180));
     commands.spawn(cam_bundle);
 }
-
-#[derive(Component, States, Clone, PartialEq, Eq, Hash, Debug, Copy)]
-enum MainState {
-    MainMenu,
-    Invaders,
-}
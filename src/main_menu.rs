@@ -1,5 +1,9 @@
 use bevy::prelude::*;
 
+use crate::actions::{Action, Bindings};
+use crate::focus::{Focus, FocusDefault};
+use crate::highscores::HighScores;
+use crate::invaders::{has_saved_game, ResumeGame};
 use crate::MainState;
 
 #[derive(Default)]
@@ -29,6 +33,11 @@ impl<T: States+Copy> Plugin for MainMenuPlugin<T> {
             })
             .add_systems(Update, mmenu_highlight.run_if(in_state(self.mystate)))
             .add_systems(Update, mmenu_invaders::<T>.run_if(in_state(self.mystate)))
+            .add_systems(Update, mmenu_continue.run_if(in_state(self.mystate)))
+            .add_systems(Update, mmenu_scores.run_if(in_state(self.mystate)))
+            .add_systems(Update, mmenu_controls.run_if(in_state(self.mystate)))
+            .add_systems(Update, mmenu_pad_help.run_if(in_state(self.mystate).and_then(resource_changed::<Gamepads>)))
+            .add_systems(Update, mmenu_focus_info.run_if(in_state(self.mystate).and_then(resource_changed::<Focus>)))
             .add_systems(OnExit(self.mystate), mmenu_onexit)
         ;
 
@@ -46,11 +55,24 @@ struct MMenuItemInvaders;
 #[derive(Component)]
 struct MMenuItemQuit;
 
+#[derive(Component)]
+struct MMenuItemContinue;
+
+#[derive(Component)]
+struct MMenuItemScores;
+
+#[derive(Component)]
+struct MMenuItemControls;
+
+#[derive(Component)]
+struct MMenuScores;
+
 
 #[derive(Resource)]
-struct MMenuStyles {
+pub struct MMenuStyles {
     item_style: TextStyle,
-    hover_color: Color,
+    /// Background of the hovered or focused item, in every menu.
+    pub hover_color: Color,
 }
 
 #[derive(Component)]
@@ -60,7 +82,14 @@ struct MMenuHoverText(String);
 struct MMenuInfo;
 
 
-fn mmenu_setup(mut commands: Commands, mut defaults: ResMut<MMenuStyles>, assets: Res<AssetServer>) {
+fn mmenu_setup(
+    mut commands: Commands,
+    mut defaults: ResMut<MMenuStyles>,
+    assets: Res<AssetServer>,
+    scores: Res<HighScores>,
+    bindings: Res<Bindings>,
+    gamepads: Res<Gamepads>,
+) {
     defaults.item_style.font = assets.load("eight-bit-dragon.otf");
 
     #[cfg(not(target_family = "wasm"))]
@@ -94,7 +123,90 @@ fn mmenu_setup(mut commands: Commands, mut defaults: ResMut<MMenuStyles>, assets
         Interaction::default(),
         MMenuItem,
         MMenuItemInvaders,
-        MMenuHoverText("Spave Invaders game.\n\nControls:\nA and D to move, LMB to shoot, Esc for pause.".into()),
+        FocusDefault,
+        MMenuHoverText(invaders_help(&bindings, gamepads.iter().next().is_some())),
+    ));
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section("Scores", defaults.item_style.clone()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(10.0),
+                top: Val::Percent(20.0),
+                ..default()
+            },
+            ..default()
+        },
+        Interaction::default(),
+        MMenuItem,
+        MMenuItemScores,
+        MMenuHoverText("".into()),
+    ));
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section("Controls", defaults.item_style.clone()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(10.0),
+                top: Val::Percent(70.0),
+                ..default()
+            },
+            ..default()
+        },
+        Interaction::default(),
+        MMenuItem,
+        MMenuItemControls,
+        MMenuHoverText("Change the keys and buttons of every action.".into()),
+    ));
+    if has_saved_game() {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section("Continue", defaults.item_style.clone()),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(10.0),
+                    top: Val::Percent(30.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Interaction::default(),
+            MMenuItem,
+            MMenuItemContinue,
+            MMenuHoverText("Continue the saved Invaders game.".into()),
+        ));
+    }
+    let mut scores_style = defaults.item_style.clone();
+    scores_style.font_size = 20.0;
+    let mut sections = vec![TextSection::new("High Scores\n\n", scores_style.clone())];
+    for (i, e) in scores.entries.iter().enumerate() {
+        let mut style = scores_style.clone();
+        if scores.last == Some(i) {
+            style.color = Color::rgb_u8(246, 153, 92);
+        }
+        sections.push(TextSection::new(
+            format!("{}. {}  {}  wave {}  {:.1}s  {}\n", i + 1, e.name, e.score, e.wave, e.time, e.date),
+            style,
+        ));
+    }
+    if scores.entries.is_empty() {
+        sections.push(TextSection::new("No scores yet", scores_style));
+    }
+    commands.spawn((
+        TextBundle {
+            text: Text::from_sections(sections),
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Percent(3.0),
+                left: Val::Percent(50.0),
+                top: Val::Percent(10.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        MMenuItem,
+        MMenuScores,
     ));
     let mut info_style = defaults.item_style.clone();
     info_style.font_size = 30.0;
@@ -110,12 +222,28 @@ fn mmenu_setup(mut commands: Commands, mut defaults: ResMut<MMenuStyles>, assets
             },
             ..default()
         },
-        Interaction::default(),
         MMenuItem,
         MMenuInfo,
     ));
 }
 
+/// Help for the Invaders item, with the gamepad controls while one is connected.
+fn invaders_help(bindings: &Bindings, pad: bool) -> String {
+    let mut help = format!("Space Invaders game.\n\nControls:\n{} and {} to move, {} to shoot, {} for pause.",
+        bindings.pad_names(Action::MoveLeft, false),
+        bindings.pad_names(Action::MoveRight, false),
+        bindings.pad_names(Action::Fire, false),
+        bindings.pad_names(Action::Pause, false));
+    if pad {
+        help += &format!("\n\nGamepad:\nLeft stick, or {} and {} to move, {} to shoot, {} for pause.",
+            bindings.pad_names(Action::MoveLeft, true),
+            bindings.pad_names(Action::MoveRight, true),
+            bindings.pad_names(Action::Fire, true),
+            bindings.pad_names(Action::Pause, true));
+    }
+    help
+}
+
 fn mmenu_invaders<T: States>(
     q: Query<&Interaction, (With<MMenuItemInvaders>, Changed<Interaction>)>,
     mut stchange: ResMut<NextState<MainState>>
@@ -128,6 +256,75 @@ fn mmenu_invaders<T: States>(
     }
 }
 
+fn mmenu_continue(
+    mut commands: Commands,
+    q: Query<&Interaction, (With<MMenuItemContinue>, Changed<Interaction>)>,
+    mut stchange: ResMut<NextState<MainState>>
+) {
+    for int in q.iter() {
+        if int == &Interaction::Pressed {
+            commands.insert_resource(ResumeGame);
+            stchange.set(MainState::Invaders)
+        }
+    }
+}
+
+fn mmenu_controls(
+    q: Query<&Interaction, (With<MMenuItemControls>, Changed<Interaction>)>,
+    mut stchange: ResMut<NextState<MainState>>
+) {
+    for int in q.iter() {
+        if int == &Interaction::Pressed {
+            stchange.set(MainState::Controls)
+        }
+    }
+}
+
+/// Shows or hides the gamepad help as pads come and go.
+fn mmenu_pad_help(
+    gamepads: Res<Gamepads>,
+    bindings: Res<Bindings>,
+    mut q: Query<(&Interaction, &mut MMenuHoverText), With<MMenuItemInvaders>>,
+    mut qinfo: Query<&mut Text, With<MMenuInfo>>,
+) {
+    let Ok((int, mut hover_text)) = q.get_single_mut() else {
+        return;
+    };
+    hover_text.0 = invaders_help(&bindings, gamepads.iter().next().is_some());
+    if let (Interaction::Hovered, Ok(mut info_text)) = (int, qinfo.get_single_mut()) {
+        info_text.sections[0].value = hover_text.0.clone();
+    }
+}
+
+/// The focused item shows its info like a hovered one.
+fn mmenu_focus_info(
+    focus: Res<Focus>,
+    q: Query<&MMenuHoverText>,
+    mut qinfo: Query<&mut Text, With<MMenuInfo>>,
+) {
+    if let (Some(Ok(hover_text)), Ok(mut info_text)) = (focus.get().map(|e| q.get(e)), qinfo.get_single_mut()) {
+        info_text.sections[0].value = hover_text.0.clone();
+    }
+}
+
+fn mmenu_scores(
+    qitems: Query<(&Interaction, Has<MMenuItemScores>), With<MMenuHoverText>>,
+    mut qscores: Query<&mut Visibility, With<MMenuScores>>,
+    scores: Res<HighScores>,
+    focus: Res<Focus>,
+) {
+    let Ok(mut visibility) = qscores.get_single_mut() else {
+        return;
+    };
+    let hovered = qitems.iter().find(|(int, _)| **int != Interaction::None)
+        .or_else(|| focus.get().and_then(|e| qitems.get(e).ok()));
+    let show = match hovered {
+        Some((_, is_scores)) => is_scores,
+        None => scores.last.is_some(),
+    };
+    *visibility = if show { Visibility::Inherited } else { Visibility::Hidden };
+}
+
 fn mmenu_highlight(
     mut q: Query<(&Interaction, &mut BackgroundColor, &MMenuHoverText), (With<MMenuItem>, Changed<Interaction>)>,
     mut qinfo: Query<&mut Text, With<MMenuInfo>>,
@@ -0,0 +1,299 @@
+use std::f32::consts::TAU;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::audio::{AddAudioSource, AudioPlugin, AudioSink, AudioSinkPlayback, AudioSourceBundle, Decodable, Source, Volume};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::invaders::events::{AlienKilled, FormationStepped, PlayerHit, ShotFired};
+use crate::storage;
+
+const STORAGE_KEY: &str = "sound.ron";
+const SAMPLE_RATE: u32 = 44100;
+
+/// Plays `PlaySound` events with sounds synthesized at startup, no audio files needed.
+///
+/// Kills, hits, shots and formation steps of a running game are subscribed to here,
+/// the gameplay systems only send their events.
+///
+/// Without an audio device Bevy only warns once and the sounds are dropped.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<PlaySound>()
+            .insert_resource(SoundSettings::load())
+            .init_resource::<SoundPaused>()
+        ;
+        if !app.is_plugin_added::<AudioPlugin>() {
+            warn!("no AudioPlugin, playing without sound");
+            return;
+        }
+        app
+            .add_audio_source::<Synth>()
+            .init_resource::<Sounds>()
+            .add_systems(Update, ((game_sounds, play_sounds).chain(), pause_sounds, cleanup_sounds))
+            .add_systems(Update, save_settings.run_if(resource_changed::<SoundSettings>))
+        ;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Sfx {
+    PlayerShot,
+    AlienShot,
+    AlienDeath,
+    PlayerHit,
+    Win,
+    Gameover,
+    /// One of the four notes of the march beat.
+    March(u8),
+}
+
+impl Sfx {
+    fn is_music(self) -> bool {
+        matches!(self, Sfx::March(_))
+    }
+}
+
+#[derive(Event)]
+pub struct PlaySound(pub Sfx);
+
+/// Volumes from 0 to 1, kept between runs.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.8,
+            sfx: 0.8,
+        }
+    }
+}
+
+impl SoundSettings {
+    fn load() -> Self {
+        let Some(text) = storage::read(STORAGE_KEY) else {
+            return default();
+        };
+        ron::from_str(&text).unwrap_or_else(|e| {
+            error!("unable to read sound settings: {}", e);
+            default()
+        })
+    }
+
+    fn volume(&self, sfx: Sfx) -> f32 {
+        self.master * if sfx.is_music() { self.music } else { self.sfx }
+    }
+}
+
+/// While set, playing sounds are paused and new ones are dropped.
+#[derive(Resource, Default)]
+pub struct SoundPaused(pub bool);
+
+/// Mono samples played through `AudioSourceBundle<Synth>`.
+#[derive(Asset, TypePath, Clone)]
+struct Synth {
+    samples: Arc<[f32]>,
+}
+
+struct SynthDecoder {
+    samples: Arc<[f32]>,
+    pos: usize,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.pos).copied();
+        self.pos += 1;
+        sample
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.pos))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.samples.len() as f32 / SAMPLE_RATE as f32))
+    }
+}
+
+impl Decodable for Synth {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> SynthDecoder {
+        SynthDecoder { samples: self.samples.clone(), pos: 0 }
+    }
+}
+
+#[derive(Resource)]
+struct Sounds(HashMap<Sfx, Handle<Synth>>);
+
+impl FromWorld for Sounds {
+    fn from_world(world: &mut World) -> Self {
+        let mut synths = world.resource_mut::<Assets<Synth>>();
+        let sounds = [
+            (Sfx::PlayerShot, sweep(880.0, 440.0, 0.12)),
+            (Sfx::AlienShot, sweep(300.0, 150.0, 0.15)),
+            (Sfx::AlienDeath, noise(0.25)),
+            (Sfx::PlayerHit, noise(0.6)),
+            (Sfx::Win, notes(&[523.0, 659.0, 784.0, 1047.0], 0.15)),
+            (Sfx::Gameover, notes(&[392.0, 330.0, 262.0, 196.0], 0.25)),
+            (Sfx::March(0), notes(&[98.0], 0.1)),
+            (Sfx::March(1), notes(&[92.5], 0.1)),
+            (Sfx::March(2), notes(&[87.3], 0.1)),
+            (Sfx::March(3), notes(&[82.4], 0.1)),
+        ];
+        Self(sounds.into_iter()
+            .map(|(sfx, samples)| (sfx, synths.add(Synth { samples: samples.into() })))
+            .collect())
+    }
+}
+
+/// Square wave gliding from one frequency to another, fading out.
+fn sweep(from: f32, to: f32, seconds: f32) -> Vec<f32> {
+    let n = (seconds * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0.0;
+    (0..n).map(|i| {
+        let t = i as f32 / n as f32;
+        phase = (phase + (from + (to - from) * t) / SAMPLE_RATE as f32) % 1.0;
+        let square = if phase < 0.5 { 0.3 } else { -0.3 };
+        square * (1.0 - t)
+    }).collect()
+}
+
+/// Decaying white noise from a fixed generator, so every run sounds the same.
+fn noise(seconds: f32) -> Vec<f32> {
+    let n = (seconds * SAMPLE_RATE as f32) as usize;
+    let mut state = 0x2545_f491_u32;
+    (0..n).map(|i| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let t = i as f32 / n as f32;
+        (state as f32 / u32::MAX as f32 - 0.5) * 0.6 * (1.0 - t) * (1.0 - t)
+    }).collect()
+}
+
+/// Plucked sine notes one after another.
+fn notes(freqs: &[f32], seconds: f32) -> Vec<f32> {
+    let n = (seconds * SAMPLE_RATE as f32) as usize;
+    freqs.iter().flat_map(|&f| (0..n).map(move |i| {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        (TAU * f * t).sin() * 0.4 * (1.0 - i as f32 / n as f32)
+    })).collect()
+}
+
+/// Sound entity, despawned after playing or if it never starts (no audio device).
+#[derive(Component)]
+struct MSound {
+    started: f32,
+}
+
+fn game_sounds(
+    mut killed: EventReader<AlienKilled>,
+    mut hit: EventReader<PlayerHit>,
+    mut shots: EventReader<ShotFired>,
+    mut steps: EventReader<FormationStepped>,
+    mut sound: EventWriter<PlaySound>,
+) {
+    for _ in killed.read() {
+        sound.send(PlaySound(Sfx::AlienDeath));
+    }
+    for _ in hit.read() {
+        sound.send(PlaySound(Sfx::PlayerHit));
+    }
+    for shot in shots.read() {
+        sound.send(PlaySound(if shot.alien { Sfx::AlienShot } else { Sfx::PlayerShot }));
+    }
+    for step in steps.read() {
+        sound.send(PlaySound(Sfx::March((step.steps % 4) as u8)));
+    }
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    mut events: EventReader<PlaySound>,
+    sounds: Res<Sounds>,
+    settings: Res<SoundSettings>,
+    paused: Res<SoundPaused>,
+    time: Res<Time<Real>>,
+) {
+    for PlaySound(sfx) in events.read() {
+        let volume = settings.volume(*sfx);
+        if paused.0 || volume <= 0.0 {
+            continue;
+        }
+        let Some(source) = sounds.0.get(sfx) else {
+            continue;
+        };
+        commands.spawn((
+            AudioSourceBundle {
+                source: source.clone(),
+                settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+            },
+            MSound { started: time.elapsed_seconds() },
+        ));
+    }
+}
+
+fn pause_sounds(
+    paused: Res<SoundPaused>,
+    sinks: Query<&AudioSink, With<MSound>>,
+) {
+    if !paused.is_changed() {
+        return;
+    }
+    for sink in sinks.iter() {
+        if paused.0 {
+            sink.pause();
+        } else {
+            sink.play();
+        }
+    }
+}
+
+fn cleanup_sounds(
+    mut commands: Commands,
+    q: Query<(Entity, &MSound), Without<AudioSink>>,
+    time: Res<Time<Real>>,
+) {
+    for (e, sound) in q.iter() {
+        if time.elapsed_seconds() - sound.started > 2.0 {
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+fn save_settings(settings: Res<SoundSettings>) {
+    if settings.is_added() {
+        return;
+    }
+    match ron::ser::to_string_pretty(&*settings, default()) {
+        Ok(text) => storage::write(STORAGE_KEY, &text),
+        Err(e) => error!("unable to save sound settings: {}", e),
+    }
+}
\ No newline at end of file
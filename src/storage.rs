@@ -0,0 +1,80 @@
+//! Small text files kept between runs: the platform data directory on desktop,
+//! `localStorage` in the browser.
+
+#[cfg(not(target_family = "wasm"))]
+mod platform {
+    use std::path::PathBuf;
+    use bevy::log::error;
+
+    fn path(key: &str) -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "invaders").map(|d| d.data_dir().join(key))
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        std::fs::read_to_string(path(key)?).ok()
+    }
+
+    pub fn write(key: &str, text: &str) {
+        let Some(path) = path(key) else {
+            error!("no data directory to save {}", key);
+            return;
+        };
+        let result = path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, text));
+        if let Err(e) = result {
+            error!("unable to save {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn remove(key: &str) {
+        if let Some(path) = path(key) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    pub fn unix_time() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod platform {
+    use bevy::log::error;
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    fn item(key: &str) -> String {
+        format!("invaders.{}", key)
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        local_storage()?.get_item(&item(key)).ok()?
+    }
+
+    pub fn write(key: &str, text: &str) {
+        let Some(storage) = local_storage() else {
+            error!("no local storage to save {}", key);
+            return;
+        };
+        if storage.set_item(&item(key), text).is_err() {
+            error!("unable to save {} to local storage", key);
+        }
+    }
+
+    pub fn remove(key: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(&item(key));
+        }
+    }
+
+    pub fn unix_time() -> u64 {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+}
+
+pub use platform::*;
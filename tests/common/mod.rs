@@ -0,0 +1,47 @@
+// each test file uses its own part of these
+#![allow(dead_code)]
+
+use bevy::app::App;
+use invaders::actions::{Action, VirtualActions};
+use invaders::invaders::{HeadlessResult, InvadersPlugin};
+use invaders::{headless_app, MainState};
+
+/// A game at the default 64 Hz lasts well under an hour.
+const MAX_UPDATES: u32 = 64 * 3600;
+
+pub fn plugin(seed: u64) -> InvadersPlugin<MainState> {
+    InvadersPlugin::for_state(MainState::Invaders).with_seed(Some(seed))
+}
+
+/// Headless app for one game, ready to `step`.
+pub fn start(plugin: InvadersPlugin<MainState>) -> App {
+    let mut app = headless_app(plugin);
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Runs one update holding `actions`, returns the result once the game is decided.
+pub fn step(app: &mut App, actions: Vec<Action>) -> Option<HeadlessResult> {
+    app.world.resource_mut::<VirtualActions>().0 = actions.into_iter().collect();
+    app.update();
+    app.world.get_resource::<HeadlessResult>().cloned()
+}
+
+/// Plays on from update `first` to the end of the game, `input` gives the actions held during each update.
+pub fn play_from(app: &mut App, first: u32, input: impl Fn(u32) -> Vec<Action>) -> HeadlessResult {
+    (first..MAX_UPDATES)
+        .find_map(|update| step(app, input(update)))
+        .unwrap_or_else(|| panic!("game not decided after {} updates", MAX_UPDATES))
+}
+
+/// Plays a headless game to its end.
+pub fn play(plugin: InvadersPlugin<MainState>, input: impl Fn(u32) -> Vec<Action>) -> HeadlessResult {
+    play_from(&mut start(plugin), 0, input)
+}
+
+/// Keeps firing while sweeping the bat from side to side.
+pub fn fire_and_sweep(update: u32) -> Vec<Action> {
+    let direction = if (update / 150) % 2 == 0 { Action::MoveLeft } else { Action::MoveRight };
+    vec![Action::Fire, direction]
+}
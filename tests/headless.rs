@@ -0,0 +1,22 @@
+mod common;
+
+use common::{fire_and_sweep, play, plugin};
+
+#[test]
+fn idle_game_ends_without_score() {
+    let result = play(plugin(42), |_| vec![]);
+    assert!(!result.won);
+    assert_eq!(result.wave, 1);
+    assert_eq!(result.score, 0);
+    assert_eq!(result.seed, 42);
+}
+
+#[test]
+fn same_seed_and_input_end_the_same() {
+    let first = play(plugin(7), fire_and_sweep);
+    let second = play(plugin(7), fire_and_sweep);
+    assert_eq!(first, second);
+    // shooting back holds the aliens off for a while
+    let idle = play(plugin(7), |_| vec![]);
+    assert!(first.ticks > idle.ticks, "{:?} against idle {:?}", first, idle);
+}
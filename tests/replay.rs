@@ -0,0 +1,24 @@
+mod common;
+
+use invaders::actions::Action;
+
+use common::{fire_and_sweep, play, plugin};
+
+#[test]
+fn replay_ends_like_the_recorded_game() {
+    let path = std::env::temp_dir().join(format!("invaders-replay-{}.ron", std::process::id()));
+    // skipping the first wave makes sure cheats are part of the replay too
+    let recorded = play(plugin(11).with_record(Some(path.clone())), |update| {
+        let mut actions = fire_and_sweep(update);
+        if (200..210).contains(&update) {
+            actions.push(Action::SkipWave);
+        }
+        actions
+    });
+    // input while replaying is ignored
+    let replayed = play(plugin(0).with_replay(Some(path.clone())), |_| vec![Action::MoveLeft]);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(recorded.wave > 1, "wave not skipped: {:?}", recorded);
+    assert_eq!(replayed, recorded);
+}
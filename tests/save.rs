@@ -0,0 +1,26 @@
+mod common;
+
+use invaders::invaders::{load_from_text, save_to_text};
+
+use common::{fire_and_sweep, play_from, plugin, start, step};
+
+const SAVED_AT: u32 = 600;
+
+#[test]
+fn loaded_game_ends_like_the_saved_one() {
+    let mut saved = start(plugin(5));
+    for update in 0..SAVED_AT {
+        assert!(step(&mut saved, fire_and_sweep(update)).is_none(), "game over before the save");
+    }
+    let text = save_to_text(&mut saved.world).unwrap();
+    let expected = play_from(&mut saved, SAVED_AT, fire_and_sweep);
+
+    // another seed, the save has to bring back the rng too
+    let mut loaded = start(plugin(6));
+    // the first updates set the game up and start it, the load then replaces it
+    for _ in 0..10 {
+        step(&mut loaded, vec![]);
+    }
+    load_from_text(&mut loaded.world, &text).unwrap();
+    assert_eq!(play_from(&mut loaded, SAVED_AT, fire_and_sweep), expected);
+}